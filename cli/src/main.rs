@@ -1,19 +1,24 @@
 //! Porter — standalone MCP gateway for MCP servers.
 //!
-//! Two subcommands:
+//! Subcommands:
 //! - `porter serve`: Streamable HTTP MCP server exposing all configured tools
 //! - `porter stdio`: STDIO transport for Claude Desktop and other STDIO-based MCP clients
+//! - `porter print-config`: print the effective config after all resolution steps
+//! - `porter bench`: measure tool-call latency through the registry
 
 use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use std::sync::Arc;
 
-use axum::Router;
 use axum::http::Request;
 use axum::response::IntoResponse;
 use clap::{Parser, Subcommand};
-use nimbus_porter::{PorterConfig, PorterMcpServer, PorterRegistry, run_hot_reload};
+use nimbus_porter::{
+    CALL_TRACE_TARGET, PorterConfig, PorterMcpServer, PorterRegistry, ReloadTarget, SessionLimit,
+    admin_routes, probe_routes, run_bench, run_hot_reload, run_idle_shutdown, run_once_shutdown,
+    serve_once, spawn_tools_changed_relay,
+};
 use rmcp::ServiceExt;
 use rmcp::transport::streamable_http_server::{
     StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
@@ -41,32 +46,123 @@ enum Commands {
         /// Path to porter.toml config file [default: ./porter.toml or ~/.config/porter/porter.toml]
         #[arg(short, long)]
         config: Option<PathBuf>,
+        /// Overlay config deep-merged over --config (repeatable, applied in order)
+        #[arg(long = "overlay", value_name = "PATH")]
+        overlays: Vec<PathBuf>,
         /// HTTP port to listen on [default: 3000, or [listen].port from config]
         #[arg(short, long)]
         port: Option<u16>,
         /// Bind address [default: 127.0.0.1, or [listen].host from config]
         #[arg(long)]
         host: Option<String>,
+        /// Only expose the server with this slug (repeatable) [default: all servers]
+        #[arg(long = "server", value_name = "SLUG")]
+        servers: Vec<String>,
+        /// Exit after this many seconds without any MCP request [default: never]
+        #[arg(long)]
+        idle_shutdown_secs: Option<u64>,
+        /// Exit non-zero if any enabled server fails its initial handshake
+        #[arg(long)]
+        strict_startup: bool,
+        /// Validate the config and server commands, then exit without serving
+        #[arg(long)]
+        check_config: bool,
+        /// Log every tools/list and tools/call request/response pair (redacted, timed)
+        #[arg(long)]
+        trace_calls: bool,
+        /// Refuse new MCP sessions with 503 once this many are open [default: unlimited]
+        #[arg(long)]
+        max_sessions: Option<usize>,
+        /// Exit after the first MCP session has been opened and closed
+        #[arg(long)]
+        once: bool,
     },
     /// Bridge all configured tools over STDIO (for Claude Desktop, etc.)
     Stdio {
         /// Path to porter.toml config file [default: ./porter.toml or ~/.config/porter/porter.toml]
         #[arg(short, long)]
         config: Option<PathBuf>,
+        /// Overlay config deep-merged over --config (repeatable, applied in order)
+        #[arg(long = "overlay", value_name = "PATH")]
+        overlays: Vec<PathBuf>,
+        /// Only expose the server with this slug (repeatable) [default: all servers]
+        #[arg(long = "server", value_name = "SLUG")]
+        servers: Vec<String>,
+        /// Log every tools/list and tools/call request/response pair (redacted, timed)
+        #[arg(long)]
+        trace_calls: bool,
+        /// Shut down all backend servers and exit as soon as the session ends
+        #[arg(long)]
+        once: bool,
+    },
+    /// Print the fully-resolved effective config (secrets redacted) and exit
+    PrintConfig {
+        /// Path to porter.toml config file [default: ./porter.toml or ~/.config/porter/porter.toml]
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Overlay config deep-merged over --config (repeatable, applied in order)
+        #[arg(long = "overlay", value_name = "PATH")]
+        overlays: Vec<PathBuf>,
+        /// Only include the server with this slug (repeatable) [default: all servers]
+        #[arg(long = "server", value_name = "SLUG")]
+        servers: Vec<String>,
+        /// Print JSON instead of TOML
+        #[arg(long)]
+        json: bool,
+    },
+    /// Call a tool repeatedly and report latency percentiles and error rate
+    Bench {
+        /// Namespaced tool name, e.g. `gh__list_repos`
+        tool: String,
+        /// Tool arguments as a JSON object
+        #[arg(long)]
+        args: Option<String>,
+        /// Total number of calls
+        #[arg(short = 'n', long, default_value_t = 100)]
+        iterations: usize,
+        /// Maximum calls in flight at once
+        #[arg(short = 'j', long, default_value_t = 1)]
+        concurrency: usize,
+        /// Seconds to wait for servers to become ready before starting
+        #[arg(long, default_value_t = 30)]
+        ready_timeout_secs: u64,
+        /// Path to porter.toml config file [default: ./porter.toml or ~/.config/porter/porter.toml]
+        #[arg(short, long)]
+        config: Option<PathBuf>,
+        /// Overlay config deep-merged over --config (repeatable, applied in order)
+        #[arg(long = "overlay", value_name = "PATH")]
+        overlays: Vec<PathBuf>,
+        /// Only start the server with this slug (repeatable) [default: all servers]
+        #[arg(long = "server", value_name = "SLUG")]
+        servers: Vec<String>,
     },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing with env filter (RUST_LOG controls verbosity)
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_writer(std::io::stderr)
-        .init();
-
     print_banner();
 
     let cli = Cli::parse();
+
+    // Initialize tracing with env filter (RUST_LOG controls verbosity);
+    // --trace-calls additionally enables the call trace target at info level
+    let mut filter = EnvFilter::from_default_env();
+    if matches!(
+        cli.command,
+        Commands::Serve {
+            trace_calls: true,
+            ..
+        } | Commands::Stdio {
+            trace_calls: true,
+            ..
+        }
+    ) {
+        filter = filter.add_directive(format!("{}=info", CALL_TRACE_TARGET).parse()?);
+    }
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
     let cancel = CancellationToken::new();
 
     // Ctrl-C handler — cancels the root token for graceful shutdown
@@ -78,59 +174,225 @@ async fn main() -> Result<()> {
     });
 
     match cli.command {
-        Commands::Serve { config, port, host } => {
+        Commands::Serve {
+            config,
+            overlays,
+            port,
+            host,
+            servers,
+            idle_shutdown_secs,
+            strict_startup,
+            check_config,
+            trace_calls,
+            max_sessions,
+            once,
+        } => {
             let config_path = resolve_config(config)?;
-            run_serve(config_path, host, port, cancel).await?;
+            if check_config {
+                return run_check_config(&config_path, &overlays, &servers).await;
+            }
+            run_serve(
+                config_path,
+                overlays,
+                servers,
+                host,
+                port,
+                ServeOptions {
+                    idle_shutdown_secs,
+                    strict_startup,
+                    trace_calls,
+                    max_sessions,
+                    once,
+                },
+                cancel,
+            )
+            .await?;
         }
-        Commands::Stdio { config } => {
+        Commands::Stdio {
+            config,
+            overlays,
+            servers,
+            trace_calls,
+            once,
+        } => {
             let config = resolve_config(config)?;
-            run_stdio(config, cancel).await?;
+            run_stdio(config, overlays, servers, trace_calls, once, cancel).await?;
+        }
+        Commands::PrintConfig {
+            config,
+            overlays,
+            servers,
+            json,
+        } => {
+            let config_path = resolve_config(config)?;
+            run_print_config(&config_path, &overlays, &servers, json).await?;
+        }
+        Commands::Bench {
+            tool,
+            args,
+            iterations,
+            concurrency,
+            ready_timeout_secs,
+            config,
+            overlays,
+            servers,
+        } => {
+            let config = resolve_config(config)?;
+            let options = BenchOptions {
+                iterations,
+                concurrency,
+                ready_timeout_secs,
+            };
+            run_bench_command(config, overlays, servers, tool, args, options).await?;
         }
     }
 
     Ok(())
 }
 
+/// Load and validate the config (including STDIO command lookup) without
+/// binding a port or spawning servers. Errors make `main` exit non-zero.
+async fn run_check_config(
+    config_path: &PathBuf,
+    overlays: &[PathBuf],
+    only_slugs: &[String],
+) -> Result<()> {
+    let mut config = load_config(config_path, overlays, only_slugs).await?;
+    config
+        .resolve()
+        .and_then(|()| config.check_commands())
+        .map_err(|e| anyhow::anyhow!("Config check failed for {:?}: {}", config_path, e))?;
+    println!("{}: ok", config_path.display());
+    Ok(())
+}
+
+/// Print the config exactly as `porter serve` would run it — overlays merged,
+/// paths resolved, `[vars]` inlined, env overrides applied — with secrets redacted.
+async fn run_print_config(
+    config_path: &PathBuf,
+    overlays: &[PathBuf],
+    only_slugs: &[String],
+    json: bool,
+) -> Result<()> {
+    let mut config = load_config(config_path, overlays, only_slugs).await?;
+    config
+        .resolve()
+        .map_err(|e| anyhow::anyhow!("Invalid config file {:?}: {}", config_path, e))?;
+    let redacted = config.redacted();
+    let rendered = if json {
+        serde_json::to_string_pretty(&redacted)?
+    } else {
+        // Via toml::Value so tables print in sorted key order
+        toml::to_string_pretty(&toml::Value::try_from(&redacted)?)?
+    };
+    println!("{}", rendered);
+    Ok(())
+}
+
+/// Behavioural switches for `porter serve` that don't affect config loading.
+struct ServeOptions {
+    /// Exit after this many seconds without client activity.
+    idle_shutdown_secs: Option<u64>,
+    /// Fail startup unless every enabled server comes up Healthy.
+    strict_startup: bool,
+    /// Log each MCP request/response pair.
+    trace_calls: bool,
+    /// Cap on concurrently open MCP sessions.
+    max_sessions: Option<usize>,
+    /// Exit once the first session has closed.
+    once: bool,
+}
+
 /// Start a Streamable HTTP MCP server exposing all configured tools.
 ///
 /// Loads porter.toml, builds PorterRegistry, wraps in PorterMcpServer,
 /// spawns a hot-reload background task, then serves via StreamableHttpService + axum.
 async fn run_serve(
     config_path: PathBuf,
+    overlays: Vec<PathBuf>,
+    only_slugs: Vec<String>,
     host_override: Option<String>,
     port_override: Option<u16>,
+    options: ServeOptions,
     cancel: CancellationToken,
 ) -> Result<()> {
-    let config = load_config(&config_path).await?;
+    if options.max_sessions == Some(0) {
+        anyhow::bail!("--max-sessions must be at least 1");
+    }
+    let config = load_config(&config_path, &overlays, &only_slugs).await?;
 
     let host = host_override.unwrap_or(config.listen.host.clone());
     let port = port_override.unwrap_or(config.listen.port);
+    let startup_timeout = config.startup_timeout();
+    let admin_token = config.admin.resolve_token();
+    if config.admin.token.is_some() && admin_token.is_none() {
+        anyhow::bail!("[admin].token references an unset or empty environment variable");
+    }
 
     let registry = PorterRegistry::from_config(config)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to build Porter registry: {}", e))?;
 
-    let server = PorterMcpServer::new(registry);
+    if options.strict_startup
+        && let Err(e) = registry.check_startup(startup_timeout).await
+    {
+        registry.shutdown().await;
+        return Err(anyhow::anyhow!("Strict startup failed: {}", e));
+    }
 
-    // Get handles for the hot-reload task
+    let mut server = PorterMcpServer::new(registry).with_config_path(&config_path);
+    if options.trace_calls {
+        server = server.with_call_tracing();
+    }
+
+    // Get handles for the hot-reload task and the probe routes
     let registry_handle = server.registry_handle();
+    let mut routes = probe_routes(registry_handle.clone());
     let peers_handle = server.peers_handle();
+    spawn_tools_changed_relay(&*registry_handle.read().await, peers_handle.clone());
 
-    // Spawn hot-reload background task — watches config file, swaps registry on change,
-    // notifies connected MCP client peers of tools-list-changed
-    tokio::spawn(run_hot_reload(
+    // One reload target for the watcher and the admin route, so their
+    // reloads share a lock and never interleave
+    let reload_target = ReloadTarget::new(
         config_path.clone(),
+        overlays,
+        only_slugs,
         registry_handle,
         peers_handle,
-        cancel.child_token(),
-    ));
+    );
+
+    // On-demand reload endpoint, only when an admin token is configured
+    if let Some(token) = admin_token {
+        routes = routes.merge(admin_routes(reload_target.clone(), token));
+    }
+
+    // Spawn hot-reload background task — watches config file, swaps registry on change,
+    // notifies connected MCP client peers of tools-list-changed
+    tokio::spawn(run_hot_reload(reload_target, cancel.child_token()));
 
     // Set up Streamable HTTP MCP service (same pattern as Navigator's run_navigator_http)
     let session_manager = Arc::new(LocalSessionManager::default());
+
+    // Optional idle shutdown — cancels the root token after a quiet period
+    // with no open sessions
+    if let Some(secs) = options.idle_shutdown_secs {
+        tokio::spawn(run_idle_shutdown(
+            server.activity_handle(),
+            session_manager.clone(),
+            std::time::Duration::from_secs(secs),
+            cancel.clone(),
+        ));
+    }
     let http_config = StreamableHttpServerConfig {
         cancellation_token: cancel.clone(),
         ..Default::default()
     };
+    if options.once {
+        tokio::spawn(run_once_shutdown(session_manager.clone(), cancel.clone()));
+    }
+    let session_limit = options
+        .max_sessions
+        .map(|max| Arc::new(SessionLimit::new(session_manager.clone(), max)));
     let server_for_factory = server.clone();
     let mcp_service = StreamableHttpService::new(
         move || Ok(server_for_factory.clone()),
@@ -138,9 +400,18 @@ async fn run_serve(
         http_config,
     );
 
-    let app = Router::new().fallback(move |req: Request<axum::body::Body>| {
+    let app = routes.fallback(move |req: Request<axum::body::Body>| {
         let svc = mcp_service.clone();
+        let session_limit = session_limit.clone();
         async move {
+            // Held while a new session initializes so the cap can't be overshot
+            let _admission = match &session_limit {
+                Some(limit) if SessionLimit::opens_session(&req) => match limit.admit().await {
+                    Ok(guard) => Some(guard),
+                    Err(response) => return response,
+                },
+                _ => None,
+            };
             match svc.oneshot(req).await {
                 Ok(resp) => resp.into_response(),
                 Err(e) => {
@@ -171,17 +442,37 @@ async fn run_serve(
 /// Bridge all configured tools over STDIO for STDIO-based MCP clients.
 ///
 /// Loads porter.toml, builds PorterRegistry, wraps in PorterMcpServer,
-/// then serves over stdin/stdout using rmcp's serve_with_ct.
-async fn run_stdio(config_path: PathBuf, cancel: CancellationToken) -> Result<()> {
-    let config = load_config(&config_path).await?;
+/// then serves over stdin/stdout using rmcp's serve_with_ct. With `once`, backend
+/// servers are shut down as soon as the session ends.
+async fn run_stdio(
+    config_path: PathBuf,
+    overlays: Vec<PathBuf>,
+    only_slugs: Vec<String>,
+    trace_calls: bool,
+    once: bool,
+    cancel: CancellationToken,
+) -> Result<()> {
+    let config = load_config(&config_path, &overlays, &only_slugs).await?;
     let registry = PorterRegistry::from_config(config)
         .await
         .map_err(|e| anyhow::anyhow!("Failed to build Porter registry: {}", e))?;
 
-    let server = PorterMcpServer::new(registry);
+    let mut server = PorterMcpServer::new(registry).with_config_path(&config_path);
+    if trace_calls {
+        server = server.with_call_tracing();
+    }
+    spawn_tools_changed_relay(
+        &*server.registry_handle().read().await,
+        server.peers_handle(),
+    );
 
     // Use rmcp's STDIO transport (same pattern as Navigator's run_navigator_stdio)
     let transport = (tokio::io::stdin(), tokio::io::stdout());
+    if once {
+        return serve_once(server, transport, cancel)
+            .await
+            .map_err(|e| anyhow::anyhow!("Porter stdio transport error: {}", e));
+    }
     let running = server
         .serve_with_ct(transport, cancel.clone())
         .await
@@ -209,6 +500,64 @@ async fn run_stdio(config_path: PathBuf, cancel: CancellationToken) -> Result<()
     Ok(())
 }
 
+/// Load-shape switches for `porter bench`.
+struct BenchOptions {
+    /// Total number of calls.
+    iterations: usize,
+    /// Maximum calls in flight at once.
+    concurrency: usize,
+    /// Seconds to wait for servers to become ready before starting.
+    ready_timeout_secs: u64,
+}
+
+/// Benchmark one tool against the configured servers and print a latency summary.
+///
+/// Loads the config like `serve`/`stdio` (overlays and `--server` filter
+/// included), builds the registry, waits (bounded) for servers to become
+/// ready, then drives `call_tool` through the shared bench harness.
+async fn run_bench_command(
+    config_path: PathBuf,
+    overlays: Vec<PathBuf>,
+    only_slugs: Vec<String>,
+    tool: String,
+    args: Option<String>,
+    options: BenchOptions,
+) -> Result<()> {
+    let BenchOptions {
+        iterations,
+        concurrency,
+        ready_timeout_secs,
+    } = options;
+    let arguments = args
+        .map(|raw| {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(&raw)
+                .map_err(|e| anyhow::anyhow!("--args must be a JSON object: {}", e))
+        })
+        .transpose()?;
+
+    let config = load_config(&config_path, &overlays, &only_slugs).await?;
+    let registry = PorterRegistry::from_config(config)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to build Porter registry: {}", e))?;
+
+    if !registry
+        .wait_until_ready(std::time::Duration::from_secs(ready_timeout_secs))
+        .await
+    {
+        tracing::warn!(
+            timeout_secs = ready_timeout_secs,
+            "not all servers ready, benchmarking anyway"
+        );
+    }
+
+    let summary = run_bench(&registry, &tool, arguments, iterations, concurrency).await;
+    registry.shutdown().await;
+
+    println!("tool:       {}", tool);
+    println!("{}", summary);
+    Ok(())
+}
+
 /// Resolve config file path: explicit flag → ./porter.toml → ~/.config/porter/porter.toml.
 fn resolve_config(explicit: Option<PathBuf>) -> Result<PathBuf> {
     if let Some(path) = explicit {
@@ -233,14 +582,16 @@ fn resolve_config(explicit: Option<PathBuf>) -> Result<PathBuf> {
     ))
 }
 
-/// Load and parse a porter.toml config file.
-async fn load_config(config_path: &PathBuf) -> Result<PorterConfig> {
-    let content = tokio::fs::read_to_string(config_path)
+/// Load the config through `PorterConfig::load`, the same pipeline hot-reload
+/// uses: `overlays` deep-merged over it, restricted to `only_slugs` when non-empty.
+async fn load_config(
+    config_path: &PathBuf,
+    overlays: &[PathBuf],
+    only_slugs: &[String],
+) -> Result<PorterConfig> {
+    PorterConfig::load(config_path, overlays, only_slugs)
         .await
-        .map_err(|e| anyhow::anyhow!("Failed to read config file {:?}: {}", config_path, e))?;
-    let config: PorterConfig = toml::from_str(&content)
-        .map_err(|e| anyhow::anyhow!("Failed to parse config file {:?}: {}", config_path, e))?;
-    Ok(config)
+        .map_err(|e| anyhow::anyhow!("Failed to load config {:?}: {}", config_path, e))
 }
 
 /// Print the Porter startup banner with raised 3D ANSI block art to stderr.
@@ -0,0 +1,171 @@
+//! Tool-call latency benchmarking for Porter.
+//!
+//! Drives `PorterRegistry::call_tool` repeatedly with bounded concurrency and
+//! summarizes the observed latencies. Used by `porter bench` for capacity
+//! planning against a live gateway configuration.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+
+use crate::PorterRegistry;
+
+/// Latency and error summary for a benchmark run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchSummary {
+    /// Number of calls issued.
+    pub iterations: usize,
+    /// Calls that returned an error (transport failure or `is_error` result).
+    pub errors: usize,
+    /// Fastest call latency.
+    pub min: Duration,
+    /// 50th-percentile call latency (nearest rank).
+    pub median: Duration,
+    /// 95th-percentile call latency (nearest rank).
+    pub p95: Duration,
+    /// Slowest call latency.
+    pub max: Duration,
+}
+
+impl BenchSummary {
+    /// Fraction of calls that failed, in `0.0..=1.0`.
+    pub fn error_rate(&self) -> f64 {
+        if self.iterations == 0 {
+            return 0.0;
+        }
+        self.errors as f64 / self.iterations as f64
+    }
+
+    /// Build a summary from per-call `(latency, failed)` samples.
+    fn from_samples(mut samples: Vec<(Duration, bool)>) -> Self {
+        samples.sort_by_key(|(latency, _)| *latency);
+        let errors = samples.iter().filter(|(_, failed)| *failed).count();
+        // Nearest-rank percentile over the sorted latencies
+        let percentile = |p: f64| -> Duration {
+            if samples.is_empty() {
+                return Duration::ZERO;
+            }
+            let rank = ((p * samples.len() as f64).ceil() as usize).clamp(1, samples.len());
+            samples[rank - 1].0
+        };
+        BenchSummary {
+            iterations: samples.len(),
+            errors,
+            min: samples.first().map_or(Duration::ZERO, |s| s.0),
+            median: percentile(0.50),
+            p95: percentile(0.95),
+            max: samples.last().map_or(Duration::ZERO, |s| s.0),
+        }
+    }
+}
+
+impl fmt::Display for BenchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "iterations: {}", self.iterations)?;
+        writeln!(
+            f,
+            "errors:     {} ({:.1}%)",
+            self.errors,
+            self.error_rate() * 100.0
+        )?;
+        writeln!(f, "min:        {:?}", self.min)?;
+        writeln!(f, "median:     {:?}", self.median)?;
+        writeln!(f, "p95:        {:?}", self.p95)?;
+        write!(f, "max:        {:?}", self.max)
+    }
+}
+
+/// Call `tool` `iterations` times with at most `concurrency` calls in flight.
+pub async fn run_bench(
+    registry: &PorterRegistry,
+    tool: &str,
+    arguments: Option<serde_json::Map<String, serde_json::Value>>,
+    iterations: usize,
+    concurrency: usize,
+) -> BenchSummary {
+    let samples: Vec<(Duration, bool)> = futures::stream::iter(0..iterations)
+        .map(|_| {
+            let arguments = arguments.clone();
+            async move {
+                let start = Instant::now();
+                let result = registry.call_tool(tool, arguments, None).await;
+                let failed = match &result {
+                    Ok(r) => r.is_error.unwrap_or(false),
+                    Err(_) => true,
+                };
+                (start.elapsed(), failed)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+        .collect()
+        .await;
+
+    tracing::debug!(tool = %tool, iterations, concurrency, "benchmark complete");
+    BenchSummary::from_samples(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::health::HealthState;
+    use crate::server::{ServerHandle, ToolCallRequest};
+    use arc_swap::ArcSwap;
+    use rmcp::model::{CallToolResult, Content};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tokio_util::sync::CancellationToken;
+
+    #[test]
+    fn test_summary_percentiles() {
+        let samples = (1..=20)
+            .map(|ms| (Duration::from_millis(ms), ms == 20))
+            .collect();
+        let summary = BenchSummary::from_samples(samples);
+        assert_eq!(summary.iterations, 20);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.min, Duration::from_millis(1));
+        assert_eq!(summary.median, Duration::from_millis(10));
+        assert_eq!(summary.p95, Duration::from_millis(19));
+        assert_eq!(summary.max, Duration::from_millis(20));
+        assert!((summary.error_rate() - 0.05).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_run_bench_against_echo_server() {
+        let (_health_tx, health_rx) = tokio::sync::watch::channel(HealthState::Healthy);
+        let (call_tx, mut call_rx) = tokio::sync::mpsc::channel::<ToolCallRequest>(8);
+        // Mock echo server: replies with the tool name it was called with
+        tokio::spawn(async move {
+            while let Some(req) = call_rx.recv().await {
+                let text = req.params.name.to_string();
+                let _ = req
+                    .response_tx
+                    .send(Ok(CallToolResult::success(vec![Content::text(text)])));
+            }
+        });
+        let handle = ServerHandle {
+            slug: "echo".to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(vec![])),
+            call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        let mut servers = HashMap::new();
+        servers.insert("echo".to_string(), handle);
+        let registry = PorterRegistry::from_handles(servers);
+
+        let summary = run_bench(&registry, "echo__say", None, 10, 3).await;
+        assert_eq!(summary.iterations, 10);
+        assert_eq!(summary.errors, 0);
+        assert!(summary.min <= summary.median);
+        assert!(summary.median <= summary.p95);
+        assert!(summary.p95 <= summary.max);
+
+        // Unknown server → every call errors
+        let summary = run_bench(&registry, "missing__say", None, 4, 2).await;
+        assert_eq!(summary.errors, 4);
+    }
+}
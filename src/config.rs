@@ -1,9 +1,10 @@
 //! Porter server configuration — deserialization and validation.
 
 use crate::error::PorterError;
-use serde::Deserialize;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use crate::redact::{REDACTED, redact_args, redact_url};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 
 /// Strip an env var reference to its variable name.
 ///
@@ -13,10 +14,41 @@ pub fn parse_env_ref(value: &str) -> Option<&str> {
     value.strip_prefix("${").and_then(|s| s.strip_suffix('}'))
 }
 
+/// Env var naming servers to disable, as comma-separated slugs.
+pub const DISABLE_ENV: &str = "PORTER_DISABLE";
+
+/// Env var naming the only servers to enable, as comma-separated slugs.
+pub const ENABLE_ONLY_ENV: &str = "PORTER_ENABLE_ONLY";
+
+/// Newest config `schema_version` this build understands.
+pub const CURRENT_SCHEMA_VERSION: u32 = 1;
+
+/// Oldest config `schema_version` this build still accepts.
+pub const MIN_SCHEMA_VERSION: u32 = 1;
+
+/// Server slug reserved for Porter's built-in tools (`porter__info`).
+const RESERVED_SLUG: &str = "porter";
+
+/// Split a comma-separated slug list, ignoring blanks. `None` if no slugs remain.
+fn slug_list(raw: &str) -> Option<Vec<&str>> {
+    let slugs: Vec<&str> = raw
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    (!slugs.is_empty()).then_some(slugs)
+}
+
+/// Strip a `${config:name}` reference to the `[vars]` name it refers to.
+pub fn parse_config_ref(value: &str) -> Option<&str> {
+    parse_env_ref(value)?.strip_prefix("config:")
+}
+
 /// Resolve a map of env-var references to their actual values.
 ///
 /// Each value must be `${VAR}` or `$VAR`. Unknown variables resolve to the
-/// empty string (same as shell `${UNSET-}`).
+/// empty string (same as shell `${UNSET-}`). Values that are not references
+/// (inlined `[vars]` literals) pass through unchanged.
 pub fn resolve_env_vars(env: &HashMap<String, String>) -> HashMap<String, String> {
     env.iter()
         .map(|(k, v)| {
@@ -36,11 +68,50 @@ pub fn resolve_env_vars(env: &HashMap<String, String>) -> HashMap<String, String
         .collect()
 }
 
+/// What changed between two configs, by server slug and field name.
+///
+/// Only names are recorded, never values, so a diff is safe to log.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ConfigDiff {
+    /// Top-level settings (everything outside `[servers]`) whose value changed.
+    pub settings: Vec<String>,
+    /// Slugs of servers only in the new config.
+    pub added: Vec<String>,
+    /// Slugs of servers only in the old config.
+    pub removed: Vec<String>,
+    /// Slug → names of changed fields, for servers in both configs.
+    pub changed: BTreeMap<String, Vec<String>>,
+}
+
+impl ConfigDiff {
+    /// Whether the two configs were identical.
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+/// Names of the top-level fields whose serialized values differ.
+fn changed_fields<T: Serialize>(old: &T, new: &T) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(old)), Ok(serde_json::Value::Object(new))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+    let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+    keys.into_iter()
+        .filter(|key| old.get(*key) != new.get(*key))
+        .cloned()
+        .collect()
+}
+
 /// HTTP listen address defaults for `porter serve`.
 ///
 /// Configured under `[listen]` in TOML. CLI flags `--host` and `--port`
 /// override these values when provided.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ListenConfig {
     #[serde(default = "default_host")]
     pub host: String,
@@ -57,6 +128,30 @@ impl Default for ListenConfig {
     }
 }
 
+/// Admin endpoint settings for `porter serve`, under `[admin]` in TOML.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AdminConfig {
+    /// Shared secret for `POST /admin/reload`, as a `${VAR}` reference so the
+    /// secret itself stays out of the config file. Unset disables the endpoint.
+    pub token: Option<String>,
+}
+
+impl AdminConfig {
+    /// Resolve the admin token from the environment.
+    ///
+    /// Returns `None` when no token is configured or the referenced variable is
+    /// unset or empty — the endpoint is never served without a secret.
+    pub fn resolve_token(&self) -> Option<String> {
+        self.resolve_token_with(|var| std::env::var(var).ok())
+    }
+
+    /// `resolve_token`, reading the referenced variable through `lookup`.
+    fn resolve_token_with(&self, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let var = parse_env_ref(self.token.as_deref()?)?;
+        lookup(var).filter(|t| !t.is_empty())
+    }
+}
+
 fn default_host() -> String {
     "127.0.0.1".to_string()
 }
@@ -66,16 +161,62 @@ fn default_port() -> u16 {
 }
 
 /// Top-level Porter configuration, parsed from TOML.
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct PorterConfig {
+    /// Config format version the file was written for. Default: current.
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+    /// Maximum STDIO process spawns (initial starts and restarts) per minute
+    /// across all servers. Default: unlimited.
+    #[serde(default)]
+    pub max_spawns_per_minute: Option<u32>,
+    /// Collapse identical tools exposed by several servers. Default: `none`.
+    #[serde(default)]
+    pub dedup_tools_by: DedupMode,
+    /// On hot-reload, wait up to this many seconds for the new registry's
+    /// servers to finish connecting before swapping it in and notifying
+    /// clients. Default: swap immediately.
+    #[serde(default)]
+    pub reload_ready_timeout_secs: Option<u64>,
+    /// Glob patterns (`*`, `?`) of exposed tool names clients may call and
+    /// see listed, regardless of which server owns them. Default: all tools.
+    #[serde(default)]
+    pub call_allowlist: Vec<String>,
+    /// Tag successful tool results with `_meta["porter/etag"]` and answer a
+    /// matching `porter/ifNoneMatch` with a not-modified marker. Costs a
+    /// serialize-and-hash of every result. Default: false.
+    #[serde(default)]
+    pub etags: bool,
     #[serde(default)]
     pub listen: ListenConfig,
     #[serde(default)]
+    pub admin: AdminConfig,
+    /// Shared values referenced from server `env` as `${config:name}`. A value
+    /// may itself be a `${VAR}` reference, resolved when the server starts.
+    #[serde(default)]
+    pub vars: HashMap<String, String>,
+    #[serde(default)]
     pub servers: HashMap<String, ServerConfig>,
+    /// Directory holding porter.toml, recorded by `load` or `resolve_paths`;
+    /// `resolve()` resolves relative `cwd`s against it.
+    #[serde(skip)]
+    pub config_dir: Option<PathBuf>,
+}
+
+/// How the registry collapses duplicate tools across servers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DedupMode {
+    /// Expose every server's tools as-is.
+    #[default]
+    None,
+    /// Tools with the same un-namespaced name and identical input schema are
+    /// exposed once, under the server whose slug sorts first.
+    Name,
 }
 
 /// Configuration for a single managed MCP server.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ServerConfig {
     pub slug: String,
     #[serde(default = "default_enabled")]
@@ -93,14 +234,80 @@ pub struct ServerConfig {
     /// Configurable MCP handshake timeout per CONTEXT.md decision, default 30s
     #[serde(default = "default_handshake_timeout_secs")]
     pub handshake_timeout_secs: u64,
+    /// Truncate tool descriptions to this many characters (with an ellipsis)
+    /// to bound the `list_tools` token cost. Default: no limit.
+    #[serde(default)]
+    pub max_description_len: Option<usize>,
+    /// Client name sent in the MCP `initialize` request. Default: rmcp's built-in name.
+    #[serde(default)]
+    pub client_name: Option<String>,
+    /// Client version sent in the MCP `initialize` request. Default: rmcp's version.
+    #[serde(default)]
+    pub client_version: Option<String>,
+    /// Advertise the `roots` client capability (Porter answers `roots/list` with
+    /// an empty list). Some servers refuse to initialize without it. Default: false.
+    #[serde(default)]
+    pub client_roots: bool,
+    /// Expose this server's tools without the `slug__` prefix. Names that
+    /// collide with another server's tools are withheld. Default: false.
+    #[serde(default)]
+    pub flat_names: bool,
+    /// Per-tool JSON fragments deep-merged onto discovered input schemas, keyed
+    /// by the server's original (un-namespaced) tool name. Objects merge key by
+    /// key; other values (including arrays like `required`) replace.
+    #[serde(default)]
+    pub schema_overrides: HashMap<String, serde_json::Value>,
+    /// Tool called periodically (with no arguments) as a liveness probe. Probe
+    /// outcomes feed the same error-rate window as client calls. Default: none.
+    #[serde(default)]
+    pub health_check_tool: Option<String>,
+    /// Seconds between `health_check_tool` probes. Default: 30.
+    #[serde(default = "default_health_check_interval_secs")]
+    pub health_check_interval_secs: u64,
+    /// Oldest acceptable negotiated MCP protocol version (`YYYY-MM-DD`). A server
+    /// negotiating an older version is marked Degraded. Default: no minimum.
+    #[serde(default)]
+    pub min_protocol_version: Option<String>,
+    /// Upper bound on a single `tools/call`, in seconds. A shorter client
+    /// deadline (`_meta["porter/timeoutMs"]`) wins. Default: no limit.
+    #[serde(default)]
+    pub call_timeout_secs: Option<u64>,
+    /// Times to re-send a tool call that failed transiently (send error or
+    /// JSON-RPC `INTERNAL_ERROR`, per `PorterError::is_retryable`). A server
+    /// may have done the work before reporting an internal error, so only set
+    /// this for servers whose tools are idempotent. Default: 0.
+    #[serde(default)]
+    pub call_retries: u32,
+    /// Retry the first spawn/connect failure at once, before entering the
+    /// exponential backoff schedule. Default: true.
+    #[serde(default = "default_enabled")]
+    pub immediate_first_retry: bool,
+    /// Slugs of servers that must be Healthy before this one is started.
+    /// Default: none.
+    #[serde(default)]
+    pub depends_on: Vec<String>,
 }
 
 /// Supported MCP transport types.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum TransportKind {
     Stdio,
+    /// Streamable HTTP (current MCP HTTP transport)
     Http,
+    /// Legacy HTTP+SSE transport used by older MCP servers
+    Sse,
+}
+
+impl TransportKind {
+    /// Human-readable transport name used in validation messages.
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            TransportKind::Stdio => "STDIO",
+            TransportKind::Http => "HTTP",
+            TransportKind::Sse => "SSE",
+        }
+    }
 }
 
 fn default_enabled() -> bool {
@@ -111,81 +318,598 @@ fn default_handshake_timeout_secs() -> u64 {
     30
 }
 
-/// Validate slug format: non-empty, alphanumeric + hyphens only, no double underscores.
-fn validate_slug_format(slug: &str) -> crate::Result<()> {
+fn default_health_check_interval_secs() -> u64 {
+    30
+}
+
+/// MCP protocol versions are dates (`YYYY-MM-DD`), so they order lexicographically.
+fn is_protocol_version(version: &str) -> bool {
+    version.len() == 10
+        && version.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            _ => c.is_ascii_digit(),
+        })
+}
+
+/// Build an `InvalidConfig` error whose message is prefixed with the field path
+/// (e.g. `servers.github.env.GITHUB_TOKEN`) so large configs are easy to debug.
+fn field_error(slug: &str, path: &str, msg: &str) -> PorterError {
+    PorterError::InvalidConfig(slug.to_string(), format!("{}: {}", path, msg))
+}
+
+/// Validate slug format: non-empty, alphanumeric + hyphens + dots only, no double underscores.
+///
+/// Dots allow organizational grouping (e.g. `aws.prod`). Slugs may not start
+/// or end with a dot. `path` is the config field path reported on error.
+fn validate_slug_format(slug: &str, path: &str) -> crate::Result<()> {
     if slug.is_empty()
         || slug.contains("__")
-        || !slug.chars().all(|c| c.is_alphanumeric() || c == '-')
+        || slug.starts_with('.')
+        || slug.ends_with('.')
+        || !slug
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '.')
     {
-        return Err(PorterError::InvalidConfig(
-            slug.to_string(),
-            "slug must be non-empty alphanumeric with hyphens, no double underscores".to_string(),
+        return Err(field_error(
+            slug,
+            path,
+            "slug must be non-empty alphanumeric with hyphens or dots, no double underscores",
         ));
     }
     Ok(())
 }
 
+/// Expand a leading `~`, `$HOME`, or `${HOME}` to the home directory, then
+/// resolve a still-relative path against `base_dir` when given.
+pub(crate) fn expand_path(raw: &Path, base_dir: Option<&Path>) -> PathBuf {
+    let expanded = match (raw.to_str(), std::env::var_os("HOME")) {
+        (Some(s), Some(home)) => {
+            let home = PathBuf::from(home);
+            if matches!(s, "~" | "$HOME" | "${HOME}") {
+                home
+            } else if let Some(rest) = ["~/", "$HOME/", "${HOME}/"]
+                .iter()
+                .find_map(|prefix| s.strip_prefix(prefix))
+            {
+                home.join(rest)
+            } else {
+                raw.to_path_buf()
+            }
+        }
+        _ => raw.to_path_buf(),
+    };
+    match base_dir {
+        Some(base) if expanded.is_relative() => base.join(expanded),
+        _ => expanded,
+    }
+}
+
+/// Return true if `command` resolves to an existing file, as a process spawn would.
+fn command_exists(command: &str, cwd: Option<&Path>) -> bool {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return match cwd {
+            Some(dir) if path.is_relative() => dir.join(path).is_file(),
+            _ => path.is_file(),
+        };
+    }
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+/// Extra time `startup_timeout` allows beyond the longest handshake timeout.
+const STARTUP_TIMEOUT_MARGIN_SECS: u64 = 5;
+
+/// Deep-merge `overlay` into `base`.
+///
+/// Tables are merged key by key, recursively; any other overlay value (including
+/// arrays) replaces the base value outright.
+fn merge_toml(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_toml(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Error for a config layer at `path` that failed to parse or merge.
+fn parse_error(path: &Path, e: impl std::fmt::Display) -> PorterError {
+    PorterError::InvalidConfig(
+        "config".to_string(),
+        format!("failed to parse '{}': {}", path.display(), e),
+    )
+}
+
+/// Read one config layer from disk for `PorterConfig::load`.
+async fn read_config_file(path: &Path) -> crate::Result<String> {
+    tokio::fs::read_to_string(path).await.map_err(|e| {
+        PorterError::InvalidConfig(
+            "config".to_string(),
+            format!("failed to read '{}': {}", path.display(), e),
+        )
+    })
+}
+
 impl PorterConfig {
+    /// Deep-merge an overlay into this config, with the same semantics as
+    /// `--overlay` files: tables (including each `[servers.<key>]`) merge key
+    /// by key and any other overlay value replaces the current one.
+    ///
+    /// The overlay is a raw TOML table rather than a parsed `PorterConfig`, so
+    /// only the fields it names override; a parsed config would carry defaults
+    /// for every field it left out.
+    pub fn overlay(&mut self, other: toml::Table) -> crate::Result<()> {
+        self.try_overlay(other).map_err(|e| {
+            PorterError::InvalidConfig("config".to_string(), format!("overlay failed: {}", e))
+        })
+    }
+
+    /// `overlay`, leaving the TOML error for the caller to attribute.
+    fn try_overlay(&mut self, other: toml::Table) -> Result<(), String> {
+        let mut merged = toml::Table::try_from(&*self).map_err(|e| e.to_string())?;
+        merge_toml(&mut merged, other);
+        let mut config: PorterConfig = merged
+            .try_into()
+            .map_err(|e: toml::de::Error| e.to_string())?;
+        config.config_dir = self.config_dir.take();
+        *self = config;
+        Ok(())
+    }
+
+    /// Load `path` with each `overlays` file deep-merged over it in order and
+    /// restrict the servers to `only_slugs` (all servers when empty).
+    ///
+    /// `path`'s directory is recorded for `resolve()`, which resolves `cwd`s
+    /// and checks they exist only after the slug filter and env overrides, so
+    /// a bad `cwd` on a filtered-out or disabled server is not an error.
+    ///
+    /// The single loading pipeline behind `porter serve`/`stdio`/`bench` and
+    /// hot-reload, so startup and reloads read config the same way.
+    pub async fn load(
+        path: &Path,
+        overlays: &[PathBuf],
+        only_slugs: &[String],
+    ) -> crate::Result<Self> {
+        let mut config: PorterConfig =
+            toml::from_str(&read_config_file(path).await?).map_err(|e| parse_error(path, e))?;
+        for overlay in overlays {
+            let table: toml::Table = toml::from_str(&read_config_file(overlay).await?)
+                .map_err(|e| parse_error(overlay, e))?;
+            config
+                .try_overlay(table)
+                .map_err(|e| parse_error(overlay, e))?;
+        }
+        config.config_dir = Some(path.parent().unwrap_or(Path::new(".")).to_path_buf());
+        config.retain_slugs(only_slugs)?;
+        Ok(config)
+    }
+
+    /// Upper bound on how long strict startup waits for every enabled server:
+    /// the longest `handshake_timeout_secs` plus a margin for tool listing retries.
+    pub fn startup_timeout(&self) -> std::time::Duration {
+        let longest = self
+            .servers
+            .values()
+            .filter(|s| s.enabled)
+            .map(|s| s.handshake_timeout_secs)
+            .max()
+            .unwrap_or(0);
+        std::time::Duration::from_secs(longest + STARTUP_TIMEOUT_MARGIN_SECS)
+    }
+
+    /// Resolve every server's `cwd`: expand `~`/`$HOME` and make relative paths
+    /// relative to `config_dir` (the directory holding porter.toml).
+    ///
+    /// Enabled servers whose resolved `cwd` is not an existing directory are an error.
+    pub fn resolve_paths(&mut self, config_dir: &Path) -> crate::Result<()> {
+        self.config_dir = Some(config_dir.to_path_buf());
+        let mut keys: Vec<String> = self.servers.keys().cloned().collect();
+        keys.sort();
+        for key in keys {
+            let Some(config) = self.servers.get_mut(&key) else {
+                continue;
+            };
+            let Some(cwd) = config.cwd.as_deref() else {
+                continue;
+            };
+            let resolved = expand_path(cwd, Some(config_dir));
+            if config.enabled && !resolved.is_dir() {
+                return Err(field_error(
+                    &config.slug,
+                    &format!("servers.{}.cwd", key),
+                    &format!("directory '{}' does not exist", resolved.display()),
+                ));
+            }
+            config.cwd = Some(resolved);
+        }
+        Ok(())
+    }
+
+    /// Verify that every enabled STDIO server's `command` can be found.
+    ///
+    /// Commands containing a path separator are checked as paths (relative to
+    /// `cwd` when set); bare names are looked up on `PATH`. Call after
+    /// `validate()`. Used by `porter serve --check-config`.
+    pub fn check_commands(&self) -> crate::Result<()> {
+        let mut keys: Vec<&String> = self.servers.keys().collect();
+        keys.sort();
+        for key in keys {
+            let config = &self.servers[key];
+            if !config.enabled || config.transport != TransportKind::Stdio {
+                continue;
+            }
+            let Some(command) = config.command.as_deref() else {
+                continue;
+            };
+            if !command_exists(command, config.cwd.as_deref()) {
+                return Err(field_error(
+                    &config.slug,
+                    &format!("servers.{}.command", key),
+                    &format!("command '{}' not found", command),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Apply every resolution step `PorterRegistry::from_config` performs before
+    /// spawning: validation, `${config:name}` inlining, the
+    /// `PORTER_ENABLE_ONLY`/`PORTER_DISABLE` overrides and, once
+    /// `resolve_paths` has recorded `config_dir`, `cwd` resolution.
+    pub fn resolve(&mut self) -> crate::Result<()> {
+        self.validate()?;
+        self.inline_config_refs();
+        self.apply_env_enabled_overrides();
+        if let Some(config_dir) = self.config_dir.clone() {
+            self.resolve_paths(&config_dir)?;
+        }
+        Ok(())
+    }
+
+    /// Server slugs ordered so that every server comes after its `depends_on`.
+    ///
+    /// Independent servers keep slug order. Fails on a dependency naming an
+    /// unknown slug or on a dependency cycle.
+    pub fn startup_order(&self) -> crate::Result<Vec<String>> {
+        let mut servers: Vec<(&String, &ServerConfig)> = self.servers.iter().collect();
+        servers.sort_by(|a, b| a.1.slug.cmp(&b.1.slug));
+        let slugs: HashSet<&str> = servers.iter().map(|(_, s)| s.slug.as_str()).collect();
+        for (key, server) in &servers {
+            if let Some(unknown) = server
+                .depends_on
+                .iter()
+                .find(|dep| !slugs.contains(dep.as_str()))
+            {
+                return Err(field_error(
+                    &server.slug,
+                    &format!("servers.{}.depends_on", key),
+                    &format!("unknown server '{}'", unknown),
+                ));
+            }
+        }
+
+        // Kahn's algorithm, always taking the smallest ready slug
+        let mut order: Vec<String> = Vec::with_capacity(servers.len());
+        let mut pending = servers;
+        while !pending.is_empty() {
+            let ready = pending.iter().position(|(_, server)| {
+                server
+                    .depends_on
+                    .iter()
+                    .all(|dep| order.iter().any(|done| done == dep))
+            });
+            match ready {
+                Some(index) => order.push(pending.remove(index).1.slug.clone()),
+                None => {
+                    let (key, server) = pending[0];
+                    let cycle: Vec<&str> = pending.iter().map(|(_, s)| s.slug.as_str()).collect();
+                    return Err(field_error(
+                        &server.slug,
+                        &format!("servers.{}.depends_on", key),
+                        &format!("dependency cycle among {}", cycle.join(", ")),
+                    ));
+                }
+            }
+        }
+        Ok(order)
+    }
+
+    /// Compare this config with `new`, server by server (matched on slug).
+    pub fn diff(&self, new: &PorterConfig) -> ConfigDiff {
+        let without_servers = |config: &PorterConfig| PorterConfig {
+            servers: HashMap::new(),
+            ..config.clone()
+        };
+        let by_slug = |config: &PorterConfig| -> BTreeMap<String, ServerConfig> {
+            config
+                .servers
+                .values()
+                .map(|server| (server.slug.clone(), server.clone()))
+                .collect()
+        };
+        let (old_servers, new_servers) = (by_slug(self), by_slug(new));
+
+        let mut diff = ConfigDiff {
+            settings: changed_fields(&without_servers(self), &without_servers(new)),
+            ..Default::default()
+        };
+        for (slug, old_server) in &old_servers {
+            match new_servers.get(slug) {
+                None => diff.removed.push(slug.clone()),
+                Some(new_server) => {
+                    let fields = changed_fields(old_server, new_server);
+                    if !fields.is_empty() {
+                        diff.changed.insert(slug.clone(), fields);
+                    }
+                }
+            }
+        }
+        diff.added = new_servers
+            .into_keys()
+            .filter(|slug| !old_servers.contains_key(slug))
+            .collect();
+        diff
+    }
+
+    /// Copy of the config safe to print: literal `[vars]` values, the `env`
+    /// values inlined from them, `url` passwords and credential-looking query
+    /// parameters, and the values of credential-looking `args` flags are
+    /// replaced; `${VAR}` references are kept.
+    pub fn redacted(&self) -> PorterConfig {
+        let redact = |values: &mut HashMap<String, String>| {
+            for value in values.values_mut() {
+                if parse_env_ref(value).is_none() {
+                    *value = REDACTED.to_string();
+                }
+            }
+        };
+        let mut config = self.clone();
+        redact(&mut config.vars);
+        for server in config.servers.values_mut() {
+            redact(&mut server.env);
+            server.url = server.url.as_deref().map(redact_url);
+            server.args = redact_args(&server.args);
+        }
+        config
+    }
+
+    /// Replace every `${config:name}` env value with the named `[vars]` entry.
+    ///
+    /// Runs after `validate()`, which guarantees every reference is defined.
+    pub(crate) fn inline_config_refs(&mut self) {
+        for server in self.servers.values_mut() {
+            for value in server.env.values_mut() {
+                if let Some(var) = parse_config_ref(value).and_then(|name| self.vars.get(name)) {
+                    *value = var.clone();
+                }
+            }
+        }
+    }
+
+    /// Override each server's `enabled` flag from `PORTER_ENABLE_ONLY` and `PORTER_DISABLE`.
+    pub(crate) fn apply_env_enabled_overrides(&mut self) {
+        self.apply_enabled_overrides_with(|var| std::env::var(var).ok());
+    }
+
+    /// `apply_env_enabled_overrides`, reading the variables through `lookup`.
+    pub(crate) fn apply_enabled_overrides_with(&mut self, lookup: impl Fn(&str) -> Option<String>) {
+        let enable_only = lookup(ENABLE_ONLY_ENV);
+        let disable = lookup(DISABLE_ENV);
+        self.apply_enabled_overrides(enable_only.as_deref(), disable.as_deref());
+    }
+
+    /// Override `enabled` flags: `enable_only` enables exactly the listed slugs
+    /// and disables the rest, then `disable` turns the listed slugs off.
+    ///
+    /// Both take precedence over the file, and `disable` wins over `enable_only`.
+    fn apply_enabled_overrides(&mut self, enable_only: Option<&str>, disable: Option<&str>) {
+        for (var, slugs) in [(ENABLE_ONLY_ENV, enable_only), (DISABLE_ENV, disable)] {
+            for slug in slugs.and_then(slug_list).unwrap_or_default() {
+                if !self.servers.values().any(|c| c.slug == slug) {
+                    tracing::warn!(var, slug, "env override names no configured server");
+                }
+            }
+        }
+        if let Some(only) = enable_only.and_then(slug_list) {
+            for server in self.servers.values_mut() {
+                server.enabled = only.contains(&server.slug.as_str());
+            }
+        }
+        if let Some(disabled) = disable.and_then(slug_list) {
+            for server in self.servers.values_mut() {
+                if disabled.contains(&server.slug.as_str()) {
+                    server.enabled = false;
+                }
+            }
+        }
+    }
+
+    /// Restrict the config to the servers whose slugs are listed in `slugs`.
+    ///
+    /// An empty list keeps every server. Returns an error naming the first
+    /// slug that does not match any configured server.
+    pub fn retain_slugs(&mut self, slugs: &[String]) -> crate::Result<()> {
+        if slugs.is_empty() {
+            return Ok(());
+        }
+        if let Some(unknown) = slugs
+            .iter()
+            .find(|s| !self.servers.values().any(|c| &c.slug == *s))
+        {
+            return Err(PorterError::InvalidConfig(
+                unknown.clone(),
+                format!("no server with slug '{}' in config", unknown),
+            ));
+        }
+        self.servers.retain(|_, c| slugs.contains(&c.slug));
+        Ok(())
+    }
+
     /// Validate the config, failing fast on misconfigurations before any servers are spawned.
+    ///
+    /// `InvalidConfig` messages are prefixed with the offending field path,
+    /// rooted at the TOML table key (e.g. `servers.github.command`).
     pub fn validate(&self) -> crate::Result<()> {
+        if let Some(version) = self.schema_version
+            && !(MIN_SCHEMA_VERSION..=CURRENT_SCHEMA_VERSION).contains(&version)
+        {
+            let hint = if version > CURRENT_SCHEMA_VERSION {
+                "written for a newer Porter; upgrade Porter"
+            } else {
+                "no longer supported; update the config"
+            };
+            return Err(field_error(
+                "config",
+                "schema_version",
+                &format!(
+                    "version {} is {} (supported: {}..={})",
+                    version, hint, MIN_SCHEMA_VERSION, CURRENT_SCHEMA_VERSION
+                ),
+            ));
+        }
+        if let Some(token) = &self.admin.token
+            && (parse_env_ref(token).is_none() || parse_config_ref(token).is_some())
+        {
+            return Err(field_error(
+                "admin",
+                "admin.token",
+                "must be a ${VAR} reference, not a literal secret",
+            ));
+        }
+
+        if self.call_allowlist.iter().any(|p| p.trim().is_empty()) {
+            return Err(field_error(
+                "config",
+                "call_allowlist",
+                "patterns must be non-empty",
+            ));
+        }
+
         // 1. Check for duplicate slugs and validate slug format for all servers
         let mut seen_slugs: HashSet<&str> = HashSet::new();
-        for config in self.servers.values() {
-            validate_slug_format(&config.slug)?;
+        for (key, config) in &self.servers {
+            validate_slug_format(&config.slug, &format!("servers.{}.slug", key))?;
+            if config.slug == RESERVED_SLUG {
+                return Err(field_error(
+                    &config.slug,
+                    &format!("servers.{}.slug", key),
+                    "slug is reserved for Porter's built-in tools",
+                ));
+            }
             if !seen_slugs.insert(config.slug.as_str()) {
                 return Err(PorterError::DuplicateSlug(config.slug.clone()));
             }
         }
+        self.startup_order()?;
 
         // 2. Validate each enabled server
-        for config in self.servers.values() {
+        for (key, config) in &self.servers {
             if !config.enabled {
                 continue;
             }
 
             let slug = &config.slug;
+            let prefix = format!("servers.{}", key);
+
+            let mut override_names: Vec<&String> = config.schema_overrides.keys().collect();
+            override_names.sort();
+            for tool in override_names {
+                if !config.schema_overrides[tool].is_object() {
+                    return Err(field_error(
+                        slug,
+                        &format!("{}.schema_overrides.{}", prefix, tool),
+                        "schema override must be a table",
+                    ));
+                }
+            }
+
+            if config.health_check_tool.is_some() && config.health_check_interval_secs == 0 {
+                return Err(field_error(
+                    slug,
+                    &format!("{}.health_check_interval_secs", prefix),
+                    "must be greater than 0",
+                ));
+            }
+
+            if let Some(version) = &config.min_protocol_version
+                && !is_protocol_version(version)
+            {
+                return Err(field_error(
+                    slug,
+                    &format!("{}.min_protocol_version", prefix),
+                    &format!("expected a YYYY-MM-DD protocol version, got '{}'", version),
+                ));
+            }
 
             // 3. Validate transport-specific required fields
             match config.transport {
                 TransportKind::Stdio => {
                     if config.command.is_none() {
-                        return Err(PorterError::InvalidConfig(
-                            slug.clone(),
-                            "STDIO transport requires 'command' field".to_string(),
+                        return Err(field_error(
+                            slug,
+                            &format!("{}.command", prefix),
+                            "STDIO transport requires 'command' field",
                         ));
                     }
                     if config.url.is_some() {
-                        return Err(PorterError::InvalidConfig(
-                            slug.clone(),
-                            "STDIO transport should not have 'url' field".to_string(),
+                        return Err(field_error(
+                            slug,
+                            &format!("{}.url", prefix),
+                            "STDIO transport should not have 'url' field",
                         ));
                     }
                 }
-                TransportKind::Http => {
-                    if config.url.is_none() {
-                        return Err(PorterError::InvalidConfig(
-                            slug.clone(),
-                            "HTTP transport requires 'url' field".to_string(),
+                TransportKind::Http | TransportKind::Sse => {
+                    let label = config.transport.label();
+                    let Some(url) = config.url.as_deref() else {
+                        return Err(field_error(
+                            slug,
+                            &format!("{}.url", prefix),
+                            &format!("{} transport requires 'url' field", label),
+                        ));
+                    };
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        return Err(field_error(
+                            slug,
+                            &format!("{}.url", prefix),
+                            &format!(
+                                "{} transport url must start with http:// or https://",
+                                label
+                            ),
                         ));
                     }
                     if config.command.is_some() {
-                        return Err(PorterError::InvalidConfig(
-                            slug.clone(),
-                            "HTTP transport should not have 'command' field".to_string(),
+                        return Err(field_error(
+                            slug,
+                            &format!("{}.command", prefix),
+                            &format!("{} transport should not have 'command' field", label),
                         ));
                     }
                 }
             }
 
-            // 4. Validate env var references: must be ${VAR}
-            for (key, value) in &config.env {
+            // 4. Validate env var references: must be ${VAR} or ${config:name}
+            for (env_key, value) in &config.env {
                 if parse_env_ref(value).is_none() {
-                    return Err(PorterError::InvalidConfig(
-                        slug.clone(),
-                        format!(
-                            "env value for key '{}' must be a ${{VAR}} reference, got '{}'",
-                            key, value
-                        ),
+                    return Err(field_error(
+                        slug,
+                        &format!("{}.env.{}", prefix, env_key),
+                        &format!("env value must be a ${{VAR}} reference, got '{}'", value),
+                    ));
+                }
+                if let Some(name) = parse_config_ref(value)
+                    && !self.vars.contains_key(name)
+                {
+                    return Err(field_error(
+                        slug,
+                        &format!("{}.env.{}", prefix, env_key),
+                        &format!("'{}' is not defined in [vars]", name),
                     ));
                 }
             }
@@ -213,6 +937,212 @@ mod tests {
         assert_eq!(parse_env_ref("${}"), Some(""));
     }
 
+    #[test]
+    fn test_config_var_references() {
+        let mut config = parse_toml(
+            r#"
+            [vars]
+            region = "eu-west-1"
+            profile = "${AWS_PROFILE}"
+
+            [servers.aws]
+            slug = "aws"
+            transport = "stdio"
+            command = "aws-mcp"
+            env.AWS_REGION = "${config:region}"
+            env.AWS_PROFILE = "${config:profile}"
+            env.HOME = "${HOME}"
+            "#,
+        );
+        assert_eq!(parse_config_ref("${config:region}"), Some("region"));
+        assert_eq!(parse_config_ref("${HOME}"), None);
+        config.validate().unwrap();
+
+        config.inline_config_refs();
+        let env = &config.servers["aws"].env;
+        assert_eq!(env["AWS_REGION"], "eu-west-1");
+        assert_eq!(env["AWS_PROFILE"], "${AWS_PROFILE}");
+        assert_eq!(env["HOME"], "${HOME}");
+        assert_eq!(resolve_env_vars(env)["AWS_REGION"], "eu-west-1");
+    }
+
+    #[test]
+    fn test_undefined_config_var_rejected() {
+        let config = parse_toml(
+            r#"
+            [servers.aws]
+            slug = "aws"
+            transport = "stdio"
+            command = "aws-mcp"
+            env.AWS_REGION = "${config:region}"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("servers.aws.env.AWS_REGION"), "{}", err);
+        assert!(err.contains("'region' is not defined in [vars]"), "{}", err);
+    }
+
+    #[test]
+    fn test_schema_version() {
+        assert!(parse_toml("schema_version = 1").validate().is_ok());
+        assert!(parse_toml("").validate().is_ok());
+
+        let err = parse_toml("schema_version = 2")
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("schema_version"), "{}", err);
+        assert!(err.contains("newer Porter"), "{}", err);
+
+        let err = parse_toml("schema_version = 0")
+            .validate()
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("no longer supported"), "{}", err);
+    }
+
+    #[test]
+    fn test_resolved_config_is_redacted() {
+        let base = r#"
+            [vars]
+            api_key = "sk-literal"
+
+            [servers.aws]
+            slug = "aws"
+            transport = "stdio"
+            command = "aws-mcp"
+            args = ["--profile", "dev", "--session-token=literal-session"]
+            env.API_KEY = "${config:api_key}"
+            env.TOKEN = "${AWS_TOKEN}"
+
+            [servers.search]
+            slug = "search"
+            transport = "http"
+            url = "https://search.example.com/mcp?apikey=literal-query&region=eu"
+            "#;
+        let overlay = r#"
+            [servers.aws]
+            handshake_timeout_secs = 90
+            "#;
+        let mut config = parse_toml(base);
+        config.overlay(toml::from_str(overlay).unwrap()).unwrap();
+        config.resolve().unwrap();
+        let printed = toml::to_string(&config.redacted()).unwrap();
+
+        assert!(
+            printed.contains("handshake_timeout_secs = 90"),
+            "{}",
+            printed
+        );
+        assert!(printed.contains(r#"TOKEN = "${AWS_TOKEN}""#), "{}", printed);
+        assert!(printed.contains(r#"API_KEY = "[REDACTED]""#), "{}", printed);
+        assert!(!printed.contains("sk-literal"), "{}", printed);
+        assert!(
+            printed.contains("--session-token=[REDACTED]"),
+            "{}",
+            printed
+        );
+        assert!(
+            printed.contains("apikey=[REDACTED]&region=eu"),
+            "{}",
+            printed
+        );
+        assert!(!printed.contains("literal-"), "{}", printed);
+        // Redaction must not touch the config that will actually run
+        assert_eq!(config.servers["aws"].env["API_KEY"], "sk-literal");
+    }
+
+    #[test]
+    fn test_diff_reports_added_server_and_changed_timeout() {
+        let old = parse_toml(
+            r#"
+            dedup_tools_by = "none"
+
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+
+            [servers.old]
+            slug = "old"
+            transport = "stdio"
+            command = "old-mcp"
+            "#,
+        );
+        let new = parse_toml(
+            r#"
+            dedup_tools_by = "name"
+
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            call_timeout_secs = 30
+
+            [servers.docs]
+            slug = "docs"
+            transport = "http"
+            url = "http://localhost:9000/mcp"
+            "#,
+        );
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, vec!["docs"]);
+        assert_eq!(diff.removed, vec!["old"]);
+        assert_eq!(
+            diff.changed,
+            BTreeMap::from([("gh".to_string(), vec!["call_timeout_secs".to_string()])])
+        );
+        assert_eq!(diff.settings, vec!["dedup_tools_by"]);
+        assert!(old.diff(&old).is_empty());
+    }
+
+    fn enabled_slugs(config: &PorterConfig) -> Vec<&str> {
+        let mut slugs: Vec<&str> = config
+            .servers
+            .values()
+            .filter(|c| c.enabled)
+            .map(|c| c.slug.as_str())
+            .collect();
+        slugs.sort();
+        slugs
+    }
+
+    #[test]
+    fn test_enabled_overrides() {
+        let toml = r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            [servers.c7]
+            slug = "c7"
+            transport = "stdio"
+            command = "c7-mcp"
+            [servers.aws]
+            slug = "aws"
+            transport = "stdio"
+            command = "aws-mcp"
+            enabled = false
+            "#;
+
+        let mut config = parse_toml(toml);
+        config.apply_enabled_overrides(None, Some("gh, unknown"));
+        assert_eq!(enabled_slugs(&config), vec!["c7"]);
+
+        let mut config = parse_toml(toml);
+        config.apply_enabled_overrides(Some("aws,gh"), None);
+        assert_eq!(enabled_slugs(&config), vec!["aws", "gh"]);
+
+        let mut config = parse_toml(toml);
+        config.apply_enabled_overrides(Some("aws,gh"), Some("gh"));
+        assert_eq!(enabled_slugs(&config), vec!["aws"]);
+
+        let mut config = parse_toml(toml);
+        config.apply_enabled_overrides(Some(" "), Some(""));
+        assert_eq!(enabled_slugs(&config), vec!["c7", "gh"]);
+    }
+
     #[test]
     fn test_resolve_env_vars() {
         // SAFETY: test-only, no concurrent threads depend on this env var.
@@ -253,7 +1183,55 @@ mod tests {
     }
 
     #[test]
-    fn test_duplicate_slug_fails() {
+    fn test_valid_sse_config() {
+        let config = parse_toml(
+            r#"
+            [servers.legacy]
+            slug = "legacy"
+            transport = "sse"
+            url = "http://localhost:8000/sse"
+            "#,
+        );
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.servers.get("legacy").unwrap().transport,
+            TransportKind::Sse
+        );
+    }
+
+    #[test]
+    fn test_sse_missing_url() {
+        let config = parse_toml(
+            r#"
+            [servers.legacy]
+            slug = "legacy"
+            transport = "sse"
+            "#,
+        );
+        let result = config.validate();
+        assert!(
+            matches!(result, Err(PorterError::InvalidConfig(slug, msg)) if slug == "legacy" && msg.contains("SSE transport requires 'url'"))
+        );
+    }
+
+    #[test]
+    fn test_sse_non_http_url_rejected() {
+        let config = parse_toml(
+            r#"
+            [servers.legacy]
+            slug = "legacy"
+            transport = "sse"
+            url = "ftp://example.com/sse"
+            "#,
+        );
+        let result = config.validate();
+        assert!(
+            matches!(result, Err(PorterError::InvalidConfig(slug, msg)) if slug == "legacy" && msg.contains("http://"))
+        );
+    }
+
+    #[test]
+    fn test_duplicate_slug_fails() {
         let config = parse_toml(
             r#"
             [servers.a]
@@ -271,6 +1249,150 @@ mod tests {
         assert!(matches!(result, Err(PorterError::DuplicateSlug(s)) if s == "same"));
     }
 
+    #[test]
+    fn test_dotted_slug_valid() {
+        let config = parse_toml(
+            r#"
+            [servers.aws-prod]
+            slug = "aws.prod"
+            transport = "stdio"
+            command = "aws-mcp"
+            "#,
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_slug_leading_or_trailing_dot_rejected() {
+        for slug in [".aws", "aws."] {
+            let config = parse_toml(&format!(
+                r#"
+                [servers.aws]
+                slug = "{slug}"
+                transport = "stdio"
+                command = "aws-mcp"
+                "#
+            ));
+            let result = config.validate();
+            assert!(
+                matches!(result, Err(PorterError::InvalidConfig(s, _)) if s == slug),
+                "slug '{slug}' should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_slug_double_underscore_rejected() {
+        let config = parse_toml(
+            r#"
+            [servers.bad]
+            slug = "aws__prod"
+            transport = "stdio"
+            command = "aws-mcp"
+            "#,
+        );
+        assert!(matches!(
+            config.validate(),
+            Err(PorterError::InvalidConfig(s, _)) if s == "aws__prod"
+        ));
+    }
+
+    #[test]
+    fn test_call_allowlist_rejects_empty_pattern() {
+        let config = parse_toml(r#"call_allowlist = ["gh__*", ""]"#);
+        assert!(matches!(
+            config.validate(),
+            Err(PorterError::InvalidConfig(_, msg)) if msg.contains("call_allowlist")
+        ));
+    }
+
+    #[test]
+    fn test_startup_order_respects_depends_on() {
+        let config = parse_toml(
+            r#"
+            [servers.proxy]
+            slug = "proxy"
+            transport = "stdio"
+            command = "proxy-mcp"
+            depends_on = ["backend"]
+
+            [servers.backend]
+            slug = "backend"
+            transport = "stdio"
+            command = "backend-mcp"
+            depends_on = ["store"]
+
+            [servers.store]
+            slug = "store"
+            transport = "stdio"
+            command = "store-mcp"
+
+            [servers.alone]
+            slug = "alone"
+            transport = "stdio"
+            command = "alone-mcp"
+            "#,
+        );
+        assert_eq!(
+            config.startup_order().unwrap(),
+            vec!["alone", "store", "backend", "proxy"]
+        );
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_depends_on_cycle_and_unknown_rejected() {
+        let cycle = parse_toml(
+            r#"
+            [servers.a]
+            slug = "a"
+            transport = "stdio"
+            command = "a-mcp"
+            depends_on = ["b"]
+
+            [servers.b]
+            slug = "b"
+            transport = "stdio"
+            command = "b-mcp"
+            depends_on = ["a"]
+            "#,
+        );
+        assert!(matches!(
+            cycle.validate(),
+            Err(PorterError::InvalidConfig(_, msg)) if msg.contains("dependency cycle among a, b")
+        ));
+
+        let unknown = parse_toml(
+            r#"
+            [servers.a]
+            slug = "a"
+            transport = "stdio"
+            command = "a-mcp"
+            depends_on = ["ghost"]
+            "#,
+        );
+        assert!(matches!(
+            unknown.validate(),
+            Err(PorterError::InvalidConfig(s, msg)) if s == "a" && msg.contains("ghost")
+        ));
+    }
+
+    #[test]
+    fn test_reserved_porter_slug_rejected() {
+        let config = parse_toml(
+            r#"
+            [servers.self]
+            slug = "porter"
+            transport = "stdio"
+            command = "porter-mcp"
+            "#,
+        );
+        assert!(matches!(
+            config.validate(),
+            Err(PorterError::InvalidConfig(s, _)) if s == "porter"
+        ));
+    }
+
     #[test]
     fn test_stdio_missing_command() {
         let config = parse_toml(
@@ -351,6 +1473,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_invalid_config_reports_field_path() {
+        let config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+
+            [servers.github.env]
+            GITHUB_TOKEN = "literal-secret"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("servers.github.env.GITHUB_TOKEN"),
+            "error should include field path: {err}"
+        );
+    }
+
+    #[test]
+    fn test_missing_command_reports_field_path() {
+        let config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh"
+            transport = "stdio"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("servers.github.command"),
+            "error should include field path: {err}"
+        );
+    }
+
+    #[test]
+    fn test_bad_slug_reports_field_path() {
+        let config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh__bad"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("servers.github.slug"),
+            "error should include field path: {err}"
+        );
+    }
+
     #[test]
     fn test_env_var_reference_valid() {
         let config = parse_toml(
@@ -401,6 +1576,521 @@ mod tests {
         assert_eq!(server.handshake_timeout_secs, 30);
     }
 
+    #[test]
+    fn test_max_description_len() {
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+
+            [servers.capped]
+            slug = "capped"
+            transport = "stdio"
+            command = "gh-mcp"
+            max_description_len = 120
+            "#,
+        );
+        assert_eq!(config.servers.get("gh").unwrap().max_description_len, None);
+        assert_eq!(
+            config.servers.get("capped").unwrap().max_description_len,
+            Some(120)
+        );
+    }
+
+    #[test]
+    fn test_retain_slugs_filters_servers() {
+        let mut config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+
+            [servers.context7]
+            slug = "c7"
+            transport = "http"
+            url = "https://mcp.context7.com/mcp"
+            "#,
+        );
+        config.retain_slugs(&["gh".to_string()]).unwrap();
+        assert_eq!(config.servers.len(), 1);
+        assert!(config.servers.contains_key("github"));
+    }
+
+    #[test]
+    fn test_retain_slugs_empty_keeps_all() {
+        let mut config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        );
+        config.retain_slugs(&[]).unwrap();
+        assert_eq!(config.servers.len(), 1);
+    }
+
+    #[test]
+    fn test_retain_slugs_unknown_errors() {
+        let mut config = parse_toml(
+            r#"
+            [servers.github]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        );
+        let result = config.retain_slugs(&["nope".to_string()]);
+        assert!(matches!(result, Err(PorterError::InvalidConfig(s, _)) if s == "nope"));
+        assert_eq!(config.servers.len(), 1, "config untouched on error");
+    }
+
+    #[test]
+    fn test_max_spawns_per_minute() {
+        let config: PorterConfig = toml::from_str("").expect("empty TOML");
+        assert_eq!(config.max_spawns_per_minute, None);
+        let config = parse_toml("max_spawns_per_minute = 20");
+        assert_eq!(config.max_spawns_per_minute, Some(20));
+    }
+
+    #[test]
+    fn test_client_identity_fields() {
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            client_name = "acme-agent"
+            client_version = "2.1.0"
+            client_roots = true
+            "#,
+        );
+        let server = config.servers.get("gh").unwrap();
+        assert_eq!(server.client_name.as_deref(), Some("acme-agent"));
+        assert_eq!(server.client_version.as_deref(), Some("2.1.0"));
+        assert!(server.client_roots);
+    }
+
+    #[test]
+    fn test_overlay_adds_server() {
+        let mut config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        );
+        let overlay: toml::Table = toml::from_str(
+            r#"
+            [servers.c7]
+            slug = "c7"
+            transport = "http"
+            url = "https://mcp.context7.com/mcp"
+            "#,
+        )
+        .unwrap();
+        config.overlay(overlay).unwrap();
+        assert_eq!(config.servers.len(), 2);
+        assert_eq!(config.servers["gh"].command.as_deref(), Some("gh-mcp"));
+    }
+
+    #[test]
+    fn test_overlay_overrides_fields() {
+        let mut config = parse_toml(
+            r#"
+            max_spawns_per_minute = 10
+
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            args = ["--verbose"]
+            env.GITHUB_TOKEN = "${GITHUB_TOKEN}"
+
+            [servers.fs]
+            slug = "fs"
+            transport = "stdio"
+            command = "fs-mcp"
+            "#,
+        );
+        let overlay: toml::Table = toml::from_str(
+            r#"
+            dedup_tools_by = "name"
+
+            [servers.gh]
+            args = ["--quiet"]
+            env.GITHUB_HOST = "${GITHUB_HOST}"
+            handshake_timeout_secs = 5
+            "#,
+        )
+        .unwrap();
+        config.overlay(overlay).unwrap();
+        assert_eq!(config.max_spawns_per_minute, Some(10), "unnamed field kept");
+        assert_eq!(config.dedup_tools_by, DedupMode::Name);
+
+        let gh = &config.servers["gh"];
+        assert_eq!(gh.command.as_deref(), Some("gh-mcp"), "base field kept");
+        assert_eq!(gh.args, vec!["--quiet"], "arrays are replaced");
+        assert_eq!(gh.env.len(), 2, "nested tables are merged");
+        assert_eq!(gh.handshake_timeout_secs, 5);
+        assert_eq!(config.servers["fs"].command.as_deref(), Some("fs-mcp"));
+
+        let bad: toml::Table =
+            toml::from_str("[servers.gh]\ntransport = \"carrier-pigeon\"").unwrap();
+        assert!(config.overlay(bad).is_err());
+        assert_eq!(
+            config.servers["gh"].transport,
+            TransportKind::Stdio,
+            "unchanged on error"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_merges_overlays_and_filters_slugs() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("porter.toml");
+        std::fs::write(
+            &base,
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            cwd = "missing"
+
+            [servers.fs]
+            slug = "fs"
+            transport = "stdio"
+            command = "fs-mcp"
+            cwd = "."
+            "#,
+        )
+        .unwrap();
+        let overlay = dir.path().join("prod.toml");
+        std::fs::write(&overlay, "[servers.fs]\ncommand = \"fs-mcp-prod\"\n").unwrap();
+
+        // gh's missing cwd is not checked once --server filters it out
+        let mut config =
+            PorterConfig::load(&base, std::slice::from_ref(&overlay), &["fs".to_string()])
+                .await
+                .unwrap();
+        assert_eq!(config.servers.len(), 1);
+        assert_eq!(config.config_dir.as_deref(), Some(dir.path()));
+        config.resolve().unwrap();
+        let fs = &config.servers["fs"];
+        assert_eq!(fs.command.as_deref(), Some("fs-mcp-prod"));
+        assert_eq!(fs.cwd.as_deref(), Some(dir.path().join(".").as_path()));
+
+        // ...nor once PORTER_DISABLE turns it off
+        let mut config = PorterConfig::load(&base, &[], &[]).await.unwrap();
+        let mut disabled = config.clone();
+        disabled.apply_enabled_overrides_with(|var| (var == DISABLE_ENV).then(|| "gh".to_string()));
+        disabled.resolve().unwrap();
+        let err = config.resolve().unwrap_err().to_string();
+        assert!(err.contains("servers.gh.cwd"), "{}", err);
+
+        let broken = dir.path().join("broken.toml");
+        std::fs::write(&broken, "not toml {{").unwrap();
+        let err = PorterConfig::load(&base, std::slice::from_ref(&broken), &[])
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(&format!("failed to parse '{}'", broken.display())),
+            "{}",
+            err
+        );
+
+        let missing = dir.path().join("missing.toml");
+        let err = PorterConfig::load(&base, &[missing], &[])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("failed to read"), "{}", err);
+        assert!(
+            PorterConfig::load(&base, &[], &["nope".to_string()])
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_startup_timeout_uses_longest_enabled_handshake() {
+        let config = parse_toml(
+            r#"
+            [servers.fast]
+            slug = "fast"
+            transport = "stdio"
+            command = "fast-mcp"
+            handshake_timeout_secs = 10
+
+            [servers.slow]
+            slug = "slow"
+            transport = "stdio"
+            command = "slow-mcp"
+            handshake_timeout_secs = 90
+            enabled = false
+            "#,
+        );
+        assert_eq!(config.startup_timeout(), std::time::Duration::from_secs(15));
+    }
+
+    #[test]
+    fn test_expand_path_tilde_and_home() {
+        let Some(home) = std::env::var_os("HOME").map(PathBuf::from) else {
+            return;
+        };
+        assert_eq!(expand_path(Path::new("~"), None), home);
+        assert_eq!(
+            expand_path(Path::new("~/projects"), Some(Path::new("/etc"))),
+            home.join("projects")
+        );
+        assert_eq!(
+            expand_path(Path::new("${HOME}/projects"), None),
+            home.join("projects")
+        );
+        // Only a leading tilde is expanded
+        assert_eq!(
+            expand_path(Path::new("a/~/b"), None),
+            PathBuf::from("a/~/b")
+        );
+    }
+
+    #[test]
+    fn test_expand_path_relative_to_base() {
+        assert_eq!(
+            expand_path(Path::new("work"), Some(Path::new("/srv/porter"))),
+            PathBuf::from("/srv/porter/work")
+        );
+        assert_eq!(
+            expand_path(Path::new("/abs/dir"), Some(Path::new("/srv/porter"))),
+            PathBuf::from("/abs/dir")
+        );
+    }
+
+    #[test]
+    fn test_resolve_paths() {
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir(base.path().join("work")).unwrap();
+        let mut config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            cwd = "work"
+
+            [servers.off]
+            slug = "off"
+            transport = "stdio"
+            command = "gh-mcp"
+            cwd = "missing"
+            enabled = false
+            "#,
+        );
+        config.resolve_paths(base.path()).unwrap();
+        assert_eq!(
+            config.servers["gh"].cwd.as_deref(),
+            Some(base.path().join("work").as_path())
+        );
+
+        // resolve() applies the recorded config dir to servers added later
+        let mut added = config.servers["gh"].clone();
+        added.slug = "gh2".to_string();
+        added.cwd = Some(PathBuf::from("work"));
+        config.servers.insert("gh2".to_string(), added);
+        config.resolve().unwrap();
+        assert_eq!(
+            config.servers["gh2"].cwd.as_deref(),
+            Some(base.path().join("work").as_path())
+        );
+
+        let mut config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            cwd = "missing"
+            "#,
+        );
+        let err = config.resolve_paths(base.path()).unwrap_err().to_string();
+        assert!(err.contains("servers.gh.cwd"), "{}", err);
+    }
+
+    #[test]
+    fn test_check_commands() {
+        let config = parse_toml(
+            r#"
+            [servers.ok]
+            slug = "ok"
+            transport = "stdio"
+            command = "sh"
+
+            [servers.off]
+            slug = "off"
+            transport = "stdio"
+            command = "porter-test-missing-binary"
+            enabled = false
+            "#,
+        );
+        config.check_commands().unwrap();
+
+        let config = parse_toml(
+            r#"
+            [servers.broken]
+            slug = "broken"
+            transport = "stdio"
+            command = "/nonexistent/porter-test-missing-binary"
+            "#,
+        );
+        let err = config.check_commands().unwrap_err().to_string();
+        assert!(err.contains("servers.broken.command"), "{}", err);
+        assert!(err.contains("not found"), "{}", err);
+    }
+
+    #[test]
+    fn test_schema_overrides() {
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+
+            [servers.gh.schema_overrides.search]
+            required = ["query"]
+            properties.query.description = "GitHub search syntax"
+            "#,
+        );
+        config.validate().unwrap();
+        let override_ = &config.servers["gh"].schema_overrides["search"];
+        assert_eq!(override_["required"], serde_json::json!(["query"]));
+
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            schema_overrides.search = "oops"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("servers.gh.schema_overrides.search"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_health_check_fields() {
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        );
+        let server = &config.servers["gh"];
+        assert_eq!(server.health_check_tool, None);
+        assert_eq!(server.health_check_interval_secs, 30);
+
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            health_check_tool = "ping"
+            health_check_interval_secs = 0
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(
+            err.contains("servers.gh.health_check_interval_secs"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn test_min_protocol_version_format() {
+        assert!(is_protocol_version("2025-03-26"));
+        assert!(!is_protocol_version("2025-3-26"));
+        assert!(!is_protocol_version("latest"));
+
+        let config = parse_toml(
+            r#"
+            [servers.gh]
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            min_protocol_version = "v2"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("servers.gh.min_protocol_version"), "{}", err);
+    }
+
+    #[test]
+    fn test_reload_ready_timeout_secs() {
+        let config = parse_toml("reload_ready_timeout_secs = 15");
+        assert_eq!(config.reload_ready_timeout_secs, Some(15));
+        assert_eq!(parse_toml("").reload_ready_timeout_secs, None);
+    }
+
+    #[test]
+    fn test_admin_token() {
+        let config = parse_toml(
+            r#"
+            [admin]
+            token = "${PORTER_TEST_ADMIN_TOKEN}"
+            "#,
+        );
+        config.validate().unwrap();
+        let env = HashMap::from([("PORTER_TEST_ADMIN_TOKEN", "s3cret"), ("EMPTY", "")]);
+        let lookup = |var: &str| env.get(var).map(|v| v.to_string());
+        assert_eq!(
+            config.admin.resolve_token_with(lookup).as_deref(),
+            Some("s3cret")
+        );
+        assert_eq!(config.admin.resolve_token_with(|_| None), None);
+        let empty = AdminConfig {
+            token: Some("${EMPTY}".to_string()),
+        };
+        assert_eq!(empty.resolve_token_with(lookup), None);
+
+        let config = parse_toml(
+            r#"
+            [admin]
+            token = "hunter2"
+            "#,
+        );
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("admin.token"), "{}", err);
+    }
+
+    #[test]
+    fn test_dedup_tools_by() {
+        let config: PorterConfig = toml::from_str("").expect("empty TOML");
+        assert_eq!(config.dedup_tools_by, DedupMode::None);
+        let config = parse_toml(r#"dedup_tools_by = "name""#);
+        assert_eq!(config.dedup_tools_by, DedupMode::Name);
+    }
+
+    #[test]
+    fn test_etags_default_off() {
+        assert!(!parse_toml("").etags);
+        assert!(parse_toml("etags = true").etags);
+    }
+
     #[test]
     fn test_listen_config_defaults() {
         let config: PorterConfig = toml::from_str("").expect("empty TOML");
@@ -29,11 +29,36 @@ pub enum PorterError {
     #[error("transport error for server '{0}': {1}")]
     Transport(String, String),
 
+    /// A named server answered a request with a JSON-RPC internal error
+    #[error("internal error from server '{0}': {1}")]
+    Internal(String, String),
+
     /// Server is shutting down
     #[error("server '{0}' shutting down")]
     ShuttingDown(String),
 }
 
+impl PorterError {
+    /// Whether retrying the failed operation could plausibly succeed.
+    ///
+    /// Transport failures (connection refused, spawn/pipe errors), handshake
+    /// failures, server internal errors and unhealthy servers are transient.
+    /// Config errors, protocol violations and shutdowns will fail the same way
+    /// again.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            PorterError::Transport(..)
+            | PorterError::Internal(..)
+            | PorterError::InitializationFailed(..)
+            | PorterError::ServerUnhealthy(..) => true,
+            PorterError::DuplicateSlug(_)
+            | PorterError::InvalidConfig(..)
+            | PorterError::Protocol(..)
+            | PorterError::ShuttingDown(_) => false,
+        }
+    }
+}
+
 /// Result type alias for Porter operations
 pub type Result<T> = std::result::Result<T, PorterError>;
 
@@ -58,4 +83,22 @@ mod tests {
             "invalid config for server 'gh': STDIO transport requires 'command' field"
         );
     }
+
+    #[test]
+    fn test_is_retryable() {
+        let s = || "gh".to_string();
+        let m = || "detail".to_string();
+        assert!(
+            PorterError::Transport(s(), "Connection refused (os error 111)".into()).is_retryable()
+        );
+        assert!(
+            PorterError::InitializationFailed(s(), "MCP handshake timed out".into()).is_retryable()
+        );
+        assert!(PorterError::ServerUnhealthy(s(), m()).is_retryable());
+        assert!(PorterError::Internal(s(), m()).is_retryable());
+        assert!(!PorterError::InvalidConfig(s(), m()).is_retryable());
+        assert!(!PorterError::Protocol(s(), "invalid JSON-RPC response".into()).is_retryable());
+        assert!(!PorterError::DuplicateSlug(s()).is_retryable());
+        assert!(!PorterError::ShuttingDown(s()).is_retryable());
+    }
 }
@@ -3,19 +3,30 @@
 //! namespaces their tools, validates config, and reports per-server health.
 //! Zero Nimbus dependencies — publishable independently to crates.io.
 
+pub mod bench;
 pub mod config;
 pub mod error;
 pub mod namespace;
+mod redact;
 pub mod registry;
 pub mod server;
 pub mod standalone;
 
+pub use bench::{BenchSummary, run_bench};
 pub use config::{
-    ListenConfig, PorterConfig, ServerConfig, TransportKind, parse_env_ref, resolve_env_vars,
+    AdminConfig, ConfigDiff, DedupMode, ListenConfig, PorterConfig, ServerConfig, TransportKind,
+    parse_config_ref, parse_env_ref, resolve_env_vars,
 };
 pub use error::{PorterError, Result};
 pub use registry::PorterRegistry;
-pub use server::ServerHandle;
 pub use server::health::HealthState;
-pub use standalone::hot_reload::run_hot_reload;
+pub use server::{CLIENT_TIMEOUT_META_KEY, ServerHandle, ServerSnapshot, TrafficStats};
+pub use standalone::admin::admin_routes;
+pub use standalone::etag::{ETAG_META_KEY, IF_NONE_MATCH_META_KEY, NOT_MODIFIED_META_KEY};
+pub use standalone::hot_reload::{ReloadTarget, run_hot_reload, spawn_tools_changed_relay};
+pub use standalone::idle::{ActivityTracker, run_idle_shutdown};
+pub use standalone::once::{run_once_shutdown, serve_once};
+pub use standalone::probes::probe_routes;
 pub use standalone::server::PorterMcpServer;
+pub use standalone::sessions::SessionLimit;
+pub use standalone::trace::CALL_TRACE_TARGET;
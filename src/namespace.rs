@@ -3,7 +3,11 @@
 //! Prefixes tool names with the server slug using double underscore separator
 //! (e.g., `gh__list_repos`) and prepends `[via slug]` to descriptions.
 
-use rmcp::model::Tool;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use rmcp::model::{JsonObject, Tool};
+use serde_json::Value;
 
 /// Prefix a tool name with the server slug using double underscore separator.
 /// E.g., slug="gh", tool="list_repos" -> "gh__list_repos"
@@ -13,6 +17,13 @@ use rmcp::model::Tool;
 pub fn namespace_tool(slug: &str, mut tool: Tool) -> Tool {
     let new_name = format!("{}__{}", slug, tool.name);
     tool.name = new_name.into();
+    attribute_tool(slug, tool)
+}
+
+/// Prepend "[via slug]" to a tool's description without renaming it.
+///
+/// Used for servers with `flat_names`, whose tools keep their original names.
+pub fn attribute_tool(slug: &str, mut tool: Tool) -> Tool {
     if let Some(desc) = tool.description.as_mut() {
         let prefixed = format!("[via {}] {}", slug, desc);
         *desc = prefixed.into();
@@ -20,12 +31,136 @@ pub fn namespace_tool(slug: &str, mut tool: Tool) -> Tool {
     tool
 }
 
+/// Deep-merge `overlay` onto a tool's input schema.
+///
+/// Objects merge key by key, recursively; any other overlay value replaces the
+/// schema's value. Used for per-tool `schema_overrides`.
+pub fn override_input_schema(mut tool: Tool, overlay: &JsonObject) -> Tool {
+    let mut schema = (*tool.input_schema).clone();
+    merge_json(&mut schema, overlay);
+    tool.input_schema = Arc::new(schema);
+    tool
+}
+
+fn merge_json(base: &mut JsonObject, overlay: &JsonObject) {
+    for (key, value) in overlay {
+        match (base.get_mut(key), value) {
+            (Some(Value::Object(base_obj)), Value::Object(overlay_obj)) => {
+                merge_json(base_obj, overlay_obj);
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+/// Remove every tool whose name is exposed more than once.
+///
+/// Flat (un-namespaced) tool names can collide across servers; ambiguous names
+/// are withheld entirely rather than routed arbitrarily. Returns the remaining
+/// tools and the sorted list of colliding names.
+pub fn drop_colliding_names(tools: Vec<Tool>) -> (Vec<Tool>, Vec<String>) {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for tool in &tools {
+        *counts.entry(tool.name.to_string()).or_default() += 1;
+    }
+    let mut colliding: Vec<String> = counts
+        .into_iter()
+        .filter(|(_, n)| *n > 1)
+        .map(|(name, _)| name)
+        .collect();
+    colliding.sort();
+    let kept = tools
+        .into_iter()
+        .filter(|t| {
+            colliding
+                .binary_search_by(|c| c.as_str().cmp(&t.name))
+                .is_err()
+        })
+        .collect();
+    (kept, colliding)
+}
+
+/// Truncate a tool description to at most `max_len` characters.
+///
+/// Truncated descriptions end with an ellipsis (`…`), which counts toward
+/// `max_len`. Descriptions already within the limit are left untouched.
+pub fn truncate_description(mut tool: Tool, max_len: usize) -> Tool {
+    if let Some(desc) = tool.description.as_mut()
+        && desc.chars().count() > max_len
+    {
+        let mut truncated: String = desc.chars().take(max_len.saturating_sub(1)).collect();
+        if max_len > 0 {
+            truncated.push('…');
+        }
+        *desc = truncated.into();
+    }
+    tool
+}
+
 /// Extract (slug, original_tool_name) from a namespaced tool name.
 /// Returns None if no double underscore separator found.
 pub fn unnamespace_tool_name(namespaced: &str) -> Option<(&str, &str)> {
     namespaced.split_once("__")
 }
 
+/// Match a tool name against a glob where `*` matches any run of characters
+/// (including none) and `?` matches exactly one.
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` and the name index it is currently absorbing up to
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    star = Some((sp, sn + 1));
+                    p = sp + 1;
+                    n = sn + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Collapse tools that share an un-namespaced name and an identical input schema.
+///
+/// `tools` must be in priority order: the first occurrence of each duplicate is
+/// kept as the primary. Returns the kept tools plus a map from each primary's
+/// namespaced name to the namespaced names it absorbed (its aliases).
+pub fn dedup_tools(tools: Vec<Tool>) -> (Vec<Tool>, HashMap<String, Vec<String>>) {
+    let mut kept: Vec<Tool> = Vec::with_capacity(tools.len());
+    let mut aliases: HashMap<String, Vec<String>> = HashMap::new();
+    for tool in tools {
+        let original = unnamespace_tool_name(&tool.name).map_or(tool.name.as_ref(), |(_, n)| n);
+        let primary = kept.iter().find(|k| {
+            let kept_original = unnamespace_tool_name(&k.name).map_or(k.name.as_ref(), |(_, n)| n);
+            kept_original == original && k.input_schema == tool.input_schema
+        });
+        match primary {
+            Some(primary) => aliases
+                .entry(primary.name.to_string())
+                .or_default()
+                .push(tool.name.to_string()),
+            None => kept.push(tool),
+        }
+    }
+    (kept, aliases)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -78,11 +213,126 @@ mod tests {
         assert_eq!(name, "list_repos");
     }
 
+    #[test]
+    fn test_dotted_slug_roundtrip() {
+        let tool = make_tool("list_buckets", Some("List buckets"));
+        let namespaced = namespace_tool("aws.prod", tool);
+        assert_eq!(namespaced.name.as_ref(), "aws.prod__list_buckets");
+        assert_eq!(
+            namespaced.description.as_deref(),
+            Some("[via aws.prod] List buckets")
+        );
+        let (slug, name) = unnamespace_tool_name(namespaced.name.as_ref()).unwrap();
+        assert_eq!(slug, "aws.prod");
+        assert_eq!(name, "list_buckets");
+    }
+
     #[test]
     fn test_unnamespace_no_separator() {
         assert!(unnamespace_tool_name("list_repos").is_none());
     }
 
+    #[test]
+    fn test_truncate_description_at_limit() {
+        let tool = make_tool("list_repos", Some("List repositories for a user"));
+        let truncated = truncate_description(tool, 10);
+        assert_eq!(truncated.description.as_deref(), Some("List repo…"));
+        assert_eq!(truncated.description.unwrap().chars().count(), 10);
+    }
+
+    #[test]
+    fn test_truncate_description_short_unchanged() {
+        let tool = make_tool("list_repos", Some("List"));
+        let truncated = truncate_description(tool, 10);
+        assert_eq!(truncated.description.as_deref(), Some("List"));
+    }
+
+    #[test]
+    fn test_truncate_description_multibyte() {
+        let tool = make_tool("t", Some("ééééé"));
+        let truncated = truncate_description(tool, 3);
+        assert_eq!(truncated.description.as_deref(), Some("éé…"));
+    }
+
+    #[test]
+    fn test_dedup_tools_collapses_identical() {
+        let tools = vec![
+            namespace_tool("a", make_tool("search", Some("Search"))),
+            namespace_tool("b", make_tool("search", Some("Search again"))),
+            namespace_tool("b", make_tool("fetch", Some("Fetch"))),
+        ];
+        let (kept, aliases) = dedup_tools(tools);
+        let names: Vec<&str> = kept.iter().map(|t| t.name.as_ref()).collect();
+        assert_eq!(names, vec!["a__search", "b__fetch"]);
+        assert_eq!(
+            aliases.get("a__search"),
+            Some(&vec!["b__search".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_attribute_tool_keeps_name() {
+        let tool = attribute_tool("gh", make_tool("list_repos", Some("List repos")));
+        assert_eq!(tool.name.as_ref(), "list_repos");
+        assert_eq!(tool.description.as_deref(), Some("[via gh] List repos"));
+    }
+
+    #[test]
+    fn test_override_input_schema_merges() {
+        let mut tool = make_tool("search", None);
+        tool.input_schema = Arc::new(
+            json!({
+                "type": "object",
+                "properties": {"q": {"type": "string"}, "limit": {"type": "integer"}},
+                "required": ["q", "limit"]
+            })
+            .as_object()
+            .unwrap()
+            .clone(),
+        );
+        let overlay = json!({
+            "properties": {"q": {"description": "Search query"}},
+            "required": ["q"]
+        });
+        let tool = override_input_schema(tool, overlay.as_object().unwrap());
+        let schema = Value::Object((*tool.input_schema).clone());
+        assert_eq!(schema["properties"]["q"]["type"], "string");
+        assert_eq!(schema["properties"]["q"]["description"], "Search query");
+        assert_eq!(schema["properties"]["limit"]["type"], "integer");
+        assert_eq!(schema["required"], json!(["q"]));
+    }
+
+    #[test]
+    fn test_drop_colliding_names() {
+        let tools = vec![
+            make_tool("search", None),
+            make_tool("fetch", None),
+            make_tool("search", Some("other backend")),
+        ];
+        let (kept, colliding) = drop_colliding_names(tools);
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].name.as_ref(), "fetch");
+        assert_eq!(colliding, vec!["search".to_string()]);
+    }
+
+    #[test]
+    fn test_dedup_tools_keeps_different_schemas() {
+        let mut other = make_tool("search", Some("Search"));
+        other.input_schema = Arc::new(
+            json!({"type": "object", "properties": {"q": {"type": "string"}}})
+                .as_object()
+                .unwrap()
+                .clone(),
+        );
+        let tools = vec![
+            namespace_tool("a", make_tool("search", Some("Search"))),
+            namespace_tool("b", other),
+        ];
+        let (kept, aliases) = dedup_tools(tools);
+        assert_eq!(kept.len(), 2);
+        assert!(aliases.is_empty());
+    }
+
     #[test]
     fn test_namespace_no_description() {
         let tool = make_tool("list_repos", None);
@@ -90,4 +340,17 @@ mod tests {
         assert_eq!(namespaced.name.as_ref(), "gh__list_repos");
         assert!(namespaced.description.is_none());
     }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("gh__*", "gh__list_repos"));
+        assert!(glob_match("*__search", "docs__search"));
+        assert!(glob_match("gh__get_?", "gh__get_x"));
+        assert!(glob_match("exact__name", "exact__name"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("gh__*", "gitlab__list_repos"));
+        assert!(!glob_match("gh__get_?", "gh__get_xy"));
+        assert!(!glob_match("exact__name", "exact__name2"));
+    }
 }
@@ -4,18 +4,28 @@
 //! aggregates their namespaced tools, routes tool calls by slug, and exposes
 //! per-server health state.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-use rmcp::model::{CallToolResult, Tool};
+use rmcp::model::{CallToolResult, Meta, Tool};
+use tokio::sync::broadcast;
 use tokio_util::sync::CancellationToken;
 
-use crate::config::{PorterConfig, TransportKind};
+use crate::config::{DedupMode, PorterConfig, ServerConfig, TransportKind};
 use crate::error::PorterError;
-use crate::namespace::unnamespace_tool_name;
-use crate::server::ServerHandle;
+use crate::namespace::{dedup_tools, drop_colliding_names, glob_match, unnamespace_tool_name};
+use crate::server::budget::{SPAWN_BUDGET_WINDOW, SpawnBudget};
 use crate::server::health::HealthState;
 use crate::server::http::spawn_http_server;
 use crate::server::stdio::spawn_stdio_server;
+use crate::server::{Dependency, ServerHandle, ServerSnapshot, TrafficStats};
+
+/// Buffered `tools_changed` events per subscriber; a lagging subscriber just
+/// sees a `Lagged` error and should treat it as "something changed".
+const TOOLS_CHANGED_CAPACITY: usize = 16;
+
+/// How often `wait_until_ready` re-checks server health.
+const READY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
 
 /// The single public entry point for Porter's multi-server MCP gateway.
 ///
@@ -24,24 +34,96 @@ use crate::server::stdio::spawn_stdio_server;
 /// correct backend based on the slug embedded in the namespaced tool name.
 pub struct PorterRegistry {
     /// Map from server slug to its managed MCP server handle.
-    servers: HashMap<String, ServerHandle>,
+    ///
+    /// A std `RwLock` is enough: the lock is only held to clone `Arc`s in or
+    /// out, never across an `.await`.
+    servers: RwLock<HashMap<String, Arc<ServerHandle>>>,
     /// Root cancellation token — cancelling this shuts down all server tasks.
     cancel: CancellationToken,
+    /// Spawn limiter shared by every STDIO server, including surgically reloaded ones.
+    spawn_budget: Arc<SpawnBudget>,
+    /// Duplicate-tool collapsing mode.
+    dedup: DedupMode,
+    /// Glob patterns of callable tool names; empty allows every tool.
+    call_allowlist: Vec<String>,
+    /// Whether routed results get ETags (`etags` in config).
+    etags: bool,
+    /// Primary namespaced tool name → collapsed duplicates, refreshed on each
+    /// `tools()` listing. Used to fail over when a primary's server is Unhealthy.
+    aliases: RwLock<HashMap<String, Vec<String>>>,
+    /// Slugs of servers whose tool list changed after they announced
+    /// `tools/list_changed` and were re-listed.
+    tools_changed: broadcast::Sender<String>,
+    /// The resolved config the registry was built from, kept in step with
+    /// `reload_single`, for diffing on hot-reload.
+    config: RwLock<PorterConfig>,
+    /// The same config before `resolve()`. `reload_single` resolves a copy of
+    /// it, since a resolved config has `[vars]` values inlined and no longer
+    /// validates.
+    unresolved: RwLock<PorterConfig>,
 }
 
 impl PorterRegistry {
     /// Build a registry from validated config, spawning all enabled servers.
     ///
-    /// Calls `config.validate()` first — returns an error without spawning
-    /// anything if config is invalid. Disabled servers are silently skipped.
+    /// Calls `config.resolve()` first — returns an error without spawning
+    /// anything if config is invalid — which also inlines `${config:name}` env
+    /// values from `[vars]` and applies the env `enabled` overrides. Disabled
+    /// servers are silently skipped.
     pub async fn from_config(config: PorterConfig) -> crate::Result<Self> {
-        config.validate()?;
+        Self::build(config, None).await
+    }
+
+    /// Like `from_config`, but the new registry inherits `previous`'s spawn
+    /// budget, so servers crash-looping across hot reloads stay throttled by
+    /// `max_spawns_per_minute`.
+    pub(crate) async fn from_config_after(
+        config: PorterConfig,
+        previous: &PorterRegistry,
+    ) -> crate::Result<Self> {
+        Self::build(config, Some(previous)).await
+    }
+
+    async fn build(
+        mut config: PorterConfig,
+        previous: Option<&PorterRegistry>,
+    ) -> crate::Result<Self> {
+        let unresolved = config.clone();
+        config.resolve()?;
+        let snapshot = config.clone();
 
         let cancel = CancellationToken::new();
-        let mut servers: HashMap<String, ServerHandle> = HashMap::new();
+        let (tools_changed, _) = broadcast::channel(TOOLS_CHANGED_CAPACITY);
+        let mut servers: HashMap<String, Arc<ServerHandle>> = HashMap::new();
+        let spawn_budget = match previous {
+            Some(previous)
+                if previous.config().max_spawns_per_minute == config.max_spawns_per_minute =>
+            {
+                previous.spawn_budget.clone()
+            }
+            Some(previous) => Arc::new(
+                previous
+                    .spawn_budget
+                    .carried_over(config.max_spawns_per_minute)
+                    .await,
+            ),
+            None => Arc::new(SpawnBudget::new(
+                config.max_spawns_per_minute,
+                SPAWN_BUDGET_WINDOW,
+            )),
+        };
 
-        // Spawn MCP servers (STDIO / HTTP)
-        for (_key, server_config) in config.servers {
+        // Spawn MCP servers (STDIO / HTTP), dependencies first
+        let order = config.startup_order()?;
+        let mut by_slug: HashMap<String, ServerConfig> = config
+            .servers
+            .into_values()
+            .map(|server| (server.slug.clone(), server))
+            .collect();
+        for slug in order {
+            let Some(server_config) = by_slug.remove(&slug) else {
+                continue;
+            };
             if !server_config.enabled {
                 tracing::debug!(
                     server = %server_config.slug,
@@ -50,47 +132,169 @@ impl PorterRegistry {
                 continue;
             }
 
-            let slug = server_config.slug.clone();
-            let child_token = cancel.child_token();
+            let dependencies = dependencies(&server_config, &servers);
+            let handle = spawn_server(
+                server_config,
+                &spawn_budget,
+                &tools_changed,
+                dependencies,
+                &cancel,
+            );
+            servers.insert(slug, Arc::new(handle));
+        }
 
-            let handle = match server_config.transport {
-                TransportKind::Stdio => {
-                    spawn_stdio_server(server_config, slug.clone(), child_token)
-                }
-                TransportKind::Http => spawn_http_server(server_config, slug.clone(), child_token),
-            };
+        Ok(PorterRegistry {
+            servers: RwLock::new(servers),
+            cancel,
+            spawn_budget,
+            dedup: config.dedup_tools_by,
+            call_allowlist: config.call_allowlist,
+            etags: config.etags,
+            aliases: RwLock::new(HashMap::new()),
+            tools_changed,
+            config: RwLock::new(snapshot),
+            unresolved: RwLock::new(unresolved),
+        })
+    }
 
-            servers.insert(slug, handle);
-        }
+    fn servers(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<ServerHandle>>> {
+        // A poisoned lock only means another thread panicked mid-swap; the map
+        // itself is always left in a consistent state, so keep serving it.
+        self.servers.read().unwrap_or_else(|e| e.into_inner())
+    }
 
-        Ok(PorterRegistry { servers, cancel })
+    fn servers_mut(&self) -> RwLockWriteGuard<'_, HashMap<String, Arc<ServerHandle>>> {
+        self.servers.write().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn handle(&self, slug: &str) -> Option<Arc<ServerHandle>> {
+        self.servers().get(slug).cloned()
     }
 
     /// Return all tools from all non-Unhealthy servers, aggregated into one list.
     ///
     /// Tools from Starting, Healthy, and Degraded MCP servers are all included —
-    /// they may be stale but are still available.
+    /// they may be stale but are still available. With `dedup_tools_by = "name"`,
+    /// identical tools are exposed once under the server whose slug sorts first.
     pub async fn tools(&self) -> Vec<Tool> {
+        let mut handles: Vec<Arc<ServerHandle>> = self.servers().values().cloned().collect();
+        handles.sort_by(|a, b| a.slug.cmp(&b.slug));
         let mut all_tools = Vec::new();
-        for handle in self.servers.values() {
+        for handle in handles {
             if handle.health() != HealthState::Unhealthy {
-                all_tools.extend(handle.tools().await);
+                all_tools.extend(handle.tools().iter().cloned());
+            }
+        }
+        let all_tools = match self.dedup {
+            DedupMode::None => all_tools,
+            DedupMode::Name => {
+                let (kept, aliases) = dedup_tools(all_tools);
+                *self.aliases.write().unwrap_or_else(|e| e.into_inner()) = aliases;
+                kept
             }
+        };
+
+        // Flat-named servers can expose clashing names; withhold them rather
+        // than advertise a tool that cannot be routed unambiguously.
+        let (kept, colliding) = drop_colliding_names(all_tools);
+        if !colliding.is_empty() {
+            tracing::error!(
+                tools = ?colliding,
+                "tool names exposed by multiple servers — disable flat_names on one of them"
+            );
         }
-        all_tools
+        kept
     }
 
-    /// Call a tool by its namespaced name, routing to the correct backend.
+    /// Map each tool `tools()` exposes to the slug of the server that owns it.
     ///
-    /// The namespaced name must have the form `slug__tool_name`. The slug is
-    /// used to look up the correct server handle. The tool call is forwarded
-    /// with the ORIGINAL (un-namespaced) tool name per the backend's expectation.
-    pub async fn call_tool(
-        &self,
-        namespaced_name: &str,
-        arguments: Option<serde_json::Map<String, serde_json::Value>>,
-    ) -> crate::Result<CallToolResult> {
-        // Parse slug from namespaced name
+    /// Built from the server handles, so flat-named tools are included; a
+    /// collapsed duplicate maps to its primary's server.
+    pub async fn tool_sources(&self) -> BTreeMap<String, String> {
+        let exposed: HashSet<String> = self
+            .tools()
+            .await
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        let mut handles: Vec<Arc<ServerHandle>> = self.servers().values().cloned().collect();
+        handles.sort_by(|a, b| a.slug.cmp(&b.slug));
+        let mut sources = BTreeMap::new();
+        for handle in handles {
+            for tool in handle.tools().iter() {
+                if exposed.contains(tool.name.as_ref()) {
+                    sources
+                        .entry(tool.name.to_string())
+                        .or_insert_with(|| handle.slug.clone());
+                }
+            }
+        }
+        sources
+    }
+
+    /// Slug of the server a call to `name` would be routed to right now.
+    pub(crate) async fn tool_owner(&self, name: &str) -> Option<String> {
+        let (handle, _) = self.route(name).await.ok()?;
+        Some(handle.slug.clone())
+    }
+
+    /// Find the flat-named server that exposes `name` verbatim, if any.
+    ///
+    /// Errors when several flat servers expose the same name, since the call
+    /// cannot be routed unambiguously.
+    async fn route_flat(&self, name: &str) -> crate::Result<Option<(Arc<ServerHandle>, String)>> {
+        let mut flat: Vec<Arc<ServerHandle>> = self
+            .servers()
+            .values()
+            .filter(|h| h.flat_names)
+            .cloned()
+            .collect();
+        flat.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+        let mut owners = Vec::new();
+        for handle in flat {
+            if handle.tools().iter().any(|t| t.name == name) {
+                owners.push(handle);
+            }
+        }
+        match owners.len() {
+            0 => Ok(None),
+            1 => {
+                let handle = owners.remove(0);
+                if handle.health() == HealthState::Unhealthy {
+                    return Err(PorterError::ServerUnhealthy(
+                        handle.slug.clone(),
+                        "server is unhealthy".to_string(),
+                    ));
+                }
+                Ok(Some((handle, name.to_string())))
+            }
+            _ => {
+                let slugs: Vec<&str> = owners.iter().map(|h| h.slug.as_str()).collect();
+                Err(PorterError::Protocol(
+                    slugs.join(","),
+                    format!(
+                        "tool name '{}' is exposed by multiple flat-named servers ({})",
+                        name,
+                        slugs.join(", ")
+                    ),
+                ))
+            }
+        }
+    }
+
+    /// Resolve a tool name to a live server handle and original tool name.
+    ///
+    /// Names exposed verbatim by a `flat_names` server route there; anything
+    /// else must be namespaced (`slug__tool`).
+    /// If the owning server is Degraded or Unhealthy and the name is a dedup
+    /// primary, the call goes to the best live candidate among the primary and
+    /// its aliases: a Healthy one if any, otherwise the lowest error rate.
+    async fn route(&self, namespaced_name: &str) -> crate::Result<(Arc<ServerHandle>, String)> {
+        if let Some(routed) = self.route_flat(namespaced_name).await? {
+            return Ok(routed);
+        }
+
         let (slug, original_name) = unnamespace_tool_name(namespaced_name).ok_or_else(|| {
             PorterError::Protocol(
                 "unknown".into(),
@@ -98,53 +302,309 @@ impl PorterRegistry {
             )
         })?;
 
-        // Look up MCP server by slug
-        let handle = self.servers.get(slug).ok_or_else(|| {
+        let handle = self.handle(slug).ok_or_else(|| {
             PorterError::Protocol(slug.to_string(), format!("no server with slug '{}'", slug))
         })?;
 
-        // Refuse calls to Unhealthy servers
-        if handle.health() == HealthState::Unhealthy {
-            return Err(PorterError::ServerUnhealthy(
+        if matches!(
+            handle.health(),
+            HealthState::Healthy | HealthState::Starting
+        ) {
+            return Ok((handle, original_name.to_string()));
+        }
+
+        let aliases = self
+            .aliases
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(namespaced_name)
+            .cloned()
+            .unwrap_or_default();
+        let alias_candidates = aliases.iter().filter_map(|alias| {
+            let (alias_slug, alias_name) = unnamespace_tool_name(alias)?;
+            Some((self.handle(alias_slug)?, alias_name.to_string()))
+        });
+        // Healthy beats Degraded; among equals the lower error rate wins, and
+        // the primary (listed first) wins ties.
+        let best = std::iter::once((handle, original_name.to_string()))
+            .chain(alias_candidates)
+            .filter(|(h, _)| h.health() != HealthState::Unhealthy)
+            .min_by(|(a, _), (b, _)| {
+                (a.health() != HealthState::Healthy)
+                    .cmp(&(b.health() != HealthState::Healthy))
+                    .then(a.error_rate().total_cmp(&b.error_rate()))
+            });
+
+        match best {
+            Some((chosen, name)) => {
+                if chosen.slug != slug {
+                    tracing::debug!(
+                        tool = %namespaced_name,
+                        server = %chosen.slug,
+                        "primary server degraded or unhealthy, routing to duplicate tool"
+                    );
+                }
+                Ok((chosen, name))
+            }
+            None => Err(PorterError::ServerUnhealthy(
                 slug.to_string(),
                 "server is unhealthy".to_string(),
-            ));
+            )),
         }
+    }
+
+    /// Call a tool by its namespaced name, routing to the correct backend.
+    ///
+    /// The namespaced name must have the form `slug__tool_name`. The slug is
+    /// used to look up the correct server handle. The tool call is forwarded
+    /// with the ORIGINAL (un-namespaced) tool name per the backend's expectation.
+    /// The client's `_meta` (progress tokens, trace context) is forwarded; only
+    /// Porter's own `porter/*` keys are stripped before the call reaches the backend.
+    pub async fn call_tool(
+        &self,
+        namespaced_name: &str,
+        arguments: Option<serde_json::Map<String, serde_json::Value>>,
+        meta: Option<Meta>,
+    ) -> crate::Result<CallToolResult> {
+        // Resolve slug → handle, refusing (or failing over from) Unhealthy servers
+        let (handle, original_name) = self.route(namespaced_name).await?;
 
         // Build call params with the original (un-namespaced) tool name
         let params = rmcp::model::CallToolRequestParams {
-            name: original_name.to_string().into(),
+            name: original_name.into(),
             arguments,
             task: None,
-            meta: None,
+            meta,
         };
 
         handle.call_tool(params).await
     }
 
+    /// Whether `call_allowlist` permits the exposed tool `name`.
+    ///
+    /// An empty allowlist permits every tool.
+    pub fn is_call_allowed(&self, name: &str) -> bool {
+        self.call_allowlist.is_empty() || self.call_allowlist.iter().any(|p| glob_match(p, name))
+    }
+
+    /// Whether routed tool results are tagged with ETags (`etags = true`).
+    pub fn etags_enabled(&self) -> bool {
+        self.etags
+    }
+
     /// Return the health state for a specific server slug, or None if not found.
     pub fn server_health(&self, slug: &str) -> Option<HealthState> {
-        self.servers.get(slug).map(|h| h.health())
+        self.servers().get(slug).map(|h| h.health())
     }
 
     /// Return a map of all server slugs to their current health states.
     pub fn all_server_health(&self) -> HashMap<String, HealthState> {
-        self.servers
+        self.servers()
             .iter()
             .map(|(slug, handle)| (slug.clone(), handle.health()))
             .collect()
     }
 
+    /// Return the health, protocol version and degraded reason of every managed
+    /// server, keyed (and ordered) by slug.
+    pub fn server_snapshots(&self) -> BTreeMap<String, ServerSnapshot> {
+        self.servers()
+            .iter()
+            .map(|(slug, handle)| (slug.clone(), handle.snapshot()))
+            .collect()
+    }
+
+    /// Return the cumulative call payload sizes of every managed server, keyed by slug.
+    pub fn all_server_traffic(&self) -> HashMap<String, TrafficStats> {
+        self.servers()
+            .iter()
+            .map(|(slug, handle)| (slug.clone(), handle.traffic()))
+            .collect()
+    }
+
+    /// Return true when every managed server has finished starting up.
+    ///
+    /// A server counts as ready once it is Healthy or Degraded. Servers that are
+    /// still Starting or have gone Unhealthy make the whole gateway not ready.
+    /// An empty registry is trivially ready.
+    pub fn is_ready(&self) -> bool {
+        self.servers()
+            .values()
+            .all(|h| matches!(h.health(), HealthState::Healthy | HealthState::Degraded))
+    }
+
+    /// Wait until `is_ready()` holds or `timeout` elapses. Returns the final readiness.
+    pub async fn wait_until_ready(&self, timeout: std::time::Duration) -> bool {
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            if self.is_ready() {
+                return true;
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return false;
+            }
+            tokio::time::sleep(READY_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Wait for each server's first connection attempt and fail unless it came up Healthy.
+    ///
+    /// Used by strict startup: a server that fails its initial handshake (or tool
+    /// listing) leaves `Starting` as Degraded or Unhealthy, and one still Starting
+    /// at the deadline timed out. Servers are checked in slug order; the first
+    /// failure is returned as `InitializationFailed`.
+    pub async fn check_startup(&self, timeout: std::time::Duration) -> crate::Result<()> {
+        let mut handles: Vec<Arc<ServerHandle>> = self.servers().values().cloned().collect();
+        handles.sort_by(|a, b| a.slug.cmp(&b.slug));
+        let deadline = tokio::time::Instant::now() + timeout;
+
+        for handle in handles {
+            let mut health_rx = handle.health_rx.clone();
+            let settled = tokio::time::timeout_at(
+                deadline,
+                health_rx.wait_for(|h| *h != HealthState::Starting),
+            )
+            .await;
+            let state = match settled {
+                Ok(Ok(state)) => *state,
+                // Run loop exited and dropped its sender
+                Ok(Err(_)) => HealthState::Unhealthy,
+                Err(_elapsed) => HealthState::Starting,
+            };
+            if state != HealthState::Healthy {
+                return Err(PorterError::InitializationFailed(
+                    handle.slug.clone(),
+                    format!("server did not start cleanly (state: {:?})", state),
+                ));
+            }
+        }
+        Ok(())
+    }
+
     /// Return a sorted list of all managed server slugs.
     pub fn server_slugs(&self) -> Vec<String> {
-        let mut slugs: Vec<String> = self.servers.keys().cloned().collect();
+        let mut slugs: Vec<String> = self.servers().keys().cloned().collect();
         slugs.sort();
         slugs
     }
 
     /// Return the total number of managed server handles (enabled at startup).
     pub fn server_count(&self) -> usize {
-        self.servers.len()
+        self.servers().len()
+    }
+
+    /// Replace a single server with one built from `new_config`, leaving all
+    /// other servers untouched.
+    ///
+    /// The old server's task is cancelled and a fresh one is spawned. If
+    /// `new_config` is disabled, the server is removed. `new_config.slug` must
+    /// match `slug` and the server must already be managed by this registry.
+    ///
+    /// `new_config` goes through the same `resolve()` as at startup, against
+    /// the registry's config: `${config:name}` refers to its `[vars]`, and the
+    /// env `enabled` overrides and `cwd` resolution apply.
+    pub async fn reload_single(&self, slug: &str, new_config: ServerConfig) -> crate::Result<()> {
+        if new_config.slug != slug {
+            return Err(PorterError::InvalidConfig(
+                slug.to_string(),
+                format!(
+                    "reload_single cannot rename server to '{}'",
+                    new_config.slug
+                ),
+            ));
+        }
+        // Resolve the new entry within the full unresolved config, exactly as
+        // `from_config` did at startup: `[vars]`, env overrides, `cwd` and
+        // `depends_on` all need the other servers and settings
+        let mut unresolved = self
+            .unresolved
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        let key = unresolved
+            .servers
+            .iter()
+            .find(|(_, s)| s.slug == slug)
+            .map_or_else(|| slug.to_string(), |(key, _)| key.clone());
+        unresolved.servers.insert(key.clone(), new_config);
+        let mut resolved = unresolved.clone();
+        resolved.resolve()?;
+        let new_config = resolved.servers[&key].clone();
+
+        let mut servers = self.servers_mut();
+        if !servers.contains_key(slug) {
+            return Err(PorterError::InvalidConfig(
+                slug.to_string(),
+                format!("no server with slug '{}'", slug),
+            ));
+        }
+
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = resolved;
+        *self.unresolved.write().unwrap_or_else(|e| e.into_inner()) = unresolved;
+        let old = if new_config.enabled {
+            let dependencies = dependencies(&new_config, &servers);
+            let handle = spawn_server(
+                new_config,
+                &self.spawn_budget,
+                &self.tools_changed,
+                dependencies,
+                &self.cancel,
+            );
+            servers.insert(slug.to_string(), Arc::new(handle))
+        } else {
+            servers.remove(slug)
+        };
+        drop(servers);
+
+        if let Some(old) = old {
+            old.cancel.cancel();
+        }
+        tracing::info!(server = %slug, "server reloaded");
+        Ok(())
+    }
+
+    /// Build a registry directly from pre-constructed handles (for tests in other modules).
+    #[cfg(test)]
+    pub(crate) fn from_handles(servers: HashMap<String, ServerHandle>) -> Self {
+        PorterRegistry {
+            servers: RwLock::new(
+                servers
+                    .into_iter()
+                    .map(|(slug, handle)| (slug, Arc::new(handle)))
+                    .collect(),
+            ),
+            cancel: CancellationToken::new(),
+            spawn_budget: Arc::new(SpawnBudget::unlimited()),
+            dedup: DedupMode::None,
+            call_allowlist: Vec::new(),
+            etags: false,
+            aliases: RwLock::new(HashMap::new()),
+            tools_changed: broadcast::channel(TOOLS_CHANGED_CAPACITY).0,
+            config: RwLock::new(PorterConfig::default()),
+            unresolved: RwLock::new(PorterConfig::default()),
+        }
+    }
+
+    /// Turn ETags on or off for a registry built with `from_handles`.
+    #[cfg(test)]
+    pub(crate) fn with_etags(mut self, etags: bool) -> Self {
+        self.etags = etags;
+        self
+    }
+
+    /// The resolved config this registry is running (empty for test registries).
+    pub fn config(&self) -> PorterConfig {
+        self.config
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Subscribe to the slugs of servers whose tool list changed at runtime.
+    ///
+    /// The channel closes once this registry and all its server tasks are gone.
+    pub fn subscribe_tools_changed(&self) -> broadcast::Receiver<String> {
+        self.tools_changed.subscribe()
     }
 
     /// Cancel all server tasks, initiating a clean shutdown.
@@ -158,13 +618,67 @@ impl PorterRegistry {
     }
 }
 
+/// Health receivers of the already-spawned servers `config` depends on.
+///
+/// A dependency that was not spawned (disabled) is skipped with a warning
+/// rather than blocking the dependent forever.
+fn dependencies(
+    config: &ServerConfig,
+    spawned: &HashMap<String, Arc<ServerHandle>>,
+) -> Vec<Dependency> {
+    config
+        .depends_on
+        .iter()
+        .filter_map(|dep| match spawned.get(dep) {
+            Some(handle) => Some((dep.clone(), handle.health_rx.clone())),
+            None => {
+                tracing::warn!(
+                    server = %config.slug,
+                    dependency = %dep,
+                    "dependency is not running, starting without it"
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Spawn one enabled server under a child of `root` and return its handle.
+///
+/// The server starts once all `dependencies` are Healthy.
+fn spawn_server(
+    config: ServerConfig,
+    spawn_budget: &Arc<SpawnBudget>,
+    tools_changed: &broadcast::Sender<String>,
+    dependencies: Vec<Dependency>,
+    root: &CancellationToken,
+) -> ServerHandle {
+    let slug = config.slug.clone();
+    let child_token = root.child_token();
+    let tools_changed = tools_changed.clone();
+    match config.transport {
+        TransportKind::Stdio => spawn_stdio_server(
+            config,
+            slug,
+            spawn_budget.clone(),
+            tools_changed,
+            dependencies,
+            child_token,
+        ),
+        TransportKind::Http | TransportKind::Sse => {
+            spawn_http_server(config, slug, tools_changed, dependencies, child_token)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{PorterConfig, ServerConfig, TransportKind};
+    use crate::config::{PorterConfig, ServerConfig};
+    use crate::server::ToolCallRequest;
+    use arc_swap::ArcSwap;
     use std::collections::HashMap;
     use std::sync::Arc;
-    use tokio::sync::RwLock;
 
     /// Build a PorterConfig programmatically (without TOML parsing).
     fn make_config(servers: Vec<ServerConfig>) -> PorterConfig {
@@ -179,17 +693,16 @@ mod tests {
     }
 
     fn stdio_config(slug: &str, enabled: bool) -> ServerConfig {
-        ServerConfig {
-            slug: slug.to_string(),
-            enabled,
-            transport: TransportKind::Stdio,
-            command: Some("echo".to_string()),
-            args: vec![],
-            env: HashMap::new(),
-            cwd: None,
-            url: None,
-            handshake_timeout_secs: 30,
-        }
+        toml::from_str(&format!(
+            r#"
+            slug = "{}"
+            transport = "stdio"
+            command = "echo"
+            enabled = {}
+            "#,
+            slug, enabled
+        ))
+        .unwrap()
     }
 
     /// Create a mock ServerHandle for testing registry routing logic.
@@ -202,12 +715,15 @@ mod tests {
     ) -> (ServerHandle, tokio::sync::watch::Sender<HealthState>) {
         let (health_tx, health_rx) = tokio::sync::watch::channel(health);
         let (call_tx, _call_rx) = tokio::sync::mpsc::channel(1);
-        let tools = Arc::new(RwLock::new(vec![]));
+        let tools = Arc::new(ArcSwap::from_pointee(vec![]));
         let handle = ServerHandle {
             slug: slug.to_string(),
             health_rx,
             tools,
             call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
         };
         (handle, health_tx)
     }
@@ -216,33 +732,17 @@ mod tests {
     async fn test_from_config_validates_duplicate_slugs() {
         // Two servers with the same slug value but different TOML keys should fail validation.
         let mut map = HashMap::new();
-        map.insert(
-            "server-a".to_string(),
-            ServerConfig {
-                slug: "same".to_string(),
-                enabled: true,
-                transport: TransportKind::Stdio,
-                command: Some("echo".to_string()),
-                args: vec![],
-                env: HashMap::new(),
-                cwd: None,
-                url: None,
-                handshake_timeout_secs: 30,
-            },
-        );
+        map.insert("server-a".to_string(), stdio_config("same", true));
         map.insert(
             "server-b".to_string(),
-            ServerConfig {
-                slug: "same".to_string(),
-                enabled: true,
-                transport: TransportKind::Http,
-                command: None,
-                args: vec![],
-                env: HashMap::new(),
-                cwd: None,
-                url: Some("http://example.com/mcp".to_string()),
-                handshake_timeout_secs: 30,
-            },
+            toml::from_str(
+                r#"
+                slug = "same"
+                transport = "http"
+                url = "http://example.com/mcp"
+                "#,
+            )
+            .unwrap(),
         );
         let config = PorterConfig {
             servers: map,
@@ -267,17 +767,341 @@ mod tests {
         assert_eq!(registry.server_count(), 1);
     }
 
+    #[tokio::test]
+    async fn test_from_config_env_disable_removes_server() {
+        let mut config = make_config(vec![
+            stdio_config("env-kept", true),
+            stdio_config("env-disabled", true),
+        ]);
+        // The disabled flag set by the override survives from_config's own
+        // (empty) env pass
+        let env = HashMap::from([(crate::config::DISABLE_ENV, "env-disabled")]);
+        config.apply_enabled_overrides_with(|var| env.get(var).map(|v| v.to_string()));
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+        assert_eq!(registry.server_slugs(), vec!["env-kept".to_string()]);
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_from_config_after_inherits_spawn_budget() {
+        let config = |max| PorterConfig {
+            max_spawns_per_minute: max,
+            ..Default::default()
+        };
+        let first = PorterRegistry::from_config(config(Some(5))).await.unwrap();
+        let same = PorterRegistry::from_config_after(config(Some(5)), &first)
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&first.spawn_budget, &same.spawn_budget));
+
+        first.spawn_budget.try_acquire().await.unwrap();
+        let lowered = PorterRegistry::from_config_after(config(Some(1)), &same)
+            .await
+            .unwrap();
+        assert!(
+            lowered.spawn_budget.try_acquire().await.is_err(),
+            "spawns before the reload count against the new limit"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_from_config_with_retained_slugs() {
+        let mut config = make_config(vec![stdio_config("gh", true), stdio_config("c7", true)]);
+        config.retain_slugs(&["gh".to_string()]).unwrap();
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+        assert_eq!(registry.server_slugs(), vec!["gh".to_string()]);
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reload_single_leaves_other_servers_untouched() {
+        let config = make_config(vec![stdio_config("a", true), stdio_config("b", true)]);
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+        let old_a = registry.handle("a").unwrap();
+        let old_b = registry.handle("b").unwrap();
+
+        let mut new_a = stdio_config("a", true);
+        new_a.handshake_timeout_secs = 5;
+        registry.reload_single("a", new_a).await.unwrap();
+
+        let cur_a = registry.handle("a").unwrap();
+        let cur_b = registry.handle("b").unwrap();
+        assert!(!Arc::ptr_eq(&old_a, &cur_a), "a should be respawned");
+        assert!(
+            old_a.cancel.is_cancelled(),
+            "old a task should be cancelled"
+        );
+        assert!(Arc::ptr_eq(&old_b, &cur_b), "b should be untouched");
+        assert!(!cur_b.cancel.is_cancelled());
+        assert!(!cur_a.cancel.is_cancelled());
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reload_single_disabled_removes_server() {
+        let config = make_config(vec![stdio_config("a", true), stdio_config("b", true)]);
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+        let old_a = registry.handle("a").unwrap();
+        registry
+            .reload_single("a", stdio_config("a", false))
+            .await
+            .unwrap();
+        assert_eq!(registry.server_slugs(), vec!["b".to_string()]);
+        assert!(old_a.cancel.is_cancelled());
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reload_single_resolves_like_startup() {
+        let mut config = make_config(vec![stdio_config("a", true), stdio_config("b", true)]);
+        config
+            .vars
+            .insert("token".to_string(), "shared-secret".to_string());
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+
+        let mut new_a = stdio_config("a", true);
+        new_a
+            .env
+            .insert("TOKEN".to_string(), "${config:token}".to_string());
+        new_a.depends_on = vec!["b".to_string()];
+        registry.reload_single("a", new_a).await.unwrap();
+
+        let config = registry.config();
+        let a = &config.servers["a"];
+        assert_eq!(a.env["TOKEN"], "shared-secret");
+        assert_eq!(a.depends_on, vec!["b".to_string()]);
+        assert_eq!(config.servers.len(), 2);
+
+        // Later reloads resolve the unresolved config again, not the one
+        // with `[vars]` already inlined
+        registry
+            .reload_single("b", stdio_config("b", true))
+            .await
+            .unwrap();
+        assert_eq!(registry.config().servers["a"].env["TOKEN"], "shared-secret");
+
+        let mut cyclic_b = stdio_config("b", true);
+        cyclic_b.depends_on = vec!["a".to_string()];
+        let err = registry
+            .reload_single("b", cyclic_b)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("dependency cycle"), "{}", err);
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_reload_single_errors() {
+        let config = make_config(vec![stdio_config("a", true)]);
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+
+        let result = registry
+            .reload_single("missing", stdio_config("missing", true))
+            .await;
+        assert!(matches!(result, Err(PorterError::InvalidConfig(s, _)) if s == "missing"));
+
+        let result = registry
+            .reload_single("a", stdio_config("renamed", true))
+            .await;
+        assert!(matches!(result, Err(PorterError::InvalidConfig(s, _)) if s == "a"));
+
+        let mut invalid = stdio_config("a", true);
+        invalid.command = None;
+        let result = registry.reload_single("a", invalid).await;
+        assert!(matches!(result, Err(PorterError::InvalidConfig(s, _)) if s == "a"));
+        registry.shutdown().await;
+    }
+
+    fn tool(name: &str) -> Tool {
+        Tool {
+            name: name.to_string().into(),
+            title: None,
+            description: None,
+            input_schema: Arc::new(serde_json::Map::new()),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+            meta: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dedup_collapses_identical_tools_and_fails_over() {
+        let (a, a_tx) = mock_server_handle("a", HealthState::Healthy);
+        a.tools.store(Arc::new(vec![tool("a__search")]));
+        let (b, _b_tx) = mock_server_handle("b", HealthState::Healthy);
+        b.tools
+            .store(Arc::new(vec![tool("b__search"), tool("b__fetch")]));
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), a);
+        servers.insert("b".to_string(), b);
+        let mut registry = PorterRegistry::from_handles(servers);
+        registry.dedup = DedupMode::Name;
+
+        let names: Vec<String> = registry
+            .tools()
+            .await
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["a__search", "b__fetch"]);
+
+        // Primary healthy → routes to a
+        let (handle, name) = registry.route("a__search").await.unwrap();
+        assert_eq!(handle.slug, "a");
+        assert_eq!(name, "search");
+
+        // Primary unhealthy → fails over to b's identical tool
+        a_tx.send(HealthState::Unhealthy).unwrap();
+        let (handle, name) = registry.route("a__search").await.unwrap();
+        assert_eq!(handle.slug, "b");
+        assert_eq!(name, "search");
+    }
+
+    #[tokio::test]
+    async fn test_degraded_routing_prefers_lower_error_rate() {
+        let (a, _a_tx) = mock_server_handle("a", HealthState::Degraded);
+        a.tools.store(Arc::new(vec![tool("a__search")]));
+        *a.status.error_rate.write().unwrap() = 0.4;
+        let (b, b_tx) = mock_server_handle("b", HealthState::Degraded);
+        b.tools.store(Arc::new(vec![tool("b__search")]));
+        *b.status.error_rate.write().unwrap() = 0.1;
+        let (c, _c_tx) = mock_server_handle("c", HealthState::Degraded);
+        c.tools.store(Arc::new(vec![tool("c__search")]));
+        *c.status.error_rate.write().unwrap() = 0.2;
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), a);
+        servers.insert("b".to_string(), b);
+        servers.insert("c".to_string(), c);
+        let mut registry = PorterRegistry::from_handles(servers);
+        registry.dedup = DedupMode::Name;
+        registry.tools().await;
+
+        // All Degraded → least-degraded member wins
+        let (handle, name) = registry.route("a__search").await.unwrap();
+        assert_eq!(handle.slug, "b");
+        assert_eq!(name, "search");
+
+        // An Unhealthy member is skipped even with the lowest recorded rate
+        b_tx.send(HealthState::Unhealthy).unwrap();
+        let (handle, _) = registry.route("a__search").await.unwrap();
+        assert_eq!(handle.slug, "c");
+    }
+
+    #[tokio::test]
+    async fn test_flat_names_exposed_and_routed() {
+        let (mut gh, _gh_tx) = mock_server_handle("gh", HealthState::Healthy);
+        gh.flat_names = true;
+        gh.tools.store(Arc::new(vec![tool("list_repos")]));
+        let (fs, _fs_tx) = mock_server_handle("fs", HealthState::Healthy);
+        fs.tools.store(Arc::new(vec![tool("fs__read_file")]));
+        let mut servers = HashMap::new();
+        servers.insert("gh".to_string(), gh);
+        servers.insert("fs".to_string(), fs);
+        let registry = PorterRegistry::from_handles(servers);
+
+        let mut names: Vec<String> = registry
+            .tools()
+            .await
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["fs__read_file", "list_repos"]);
+
+        let (handle, name) = registry.route("list_repos").await.unwrap();
+        assert_eq!(handle.slug, "gh");
+        assert_eq!(name, "list_repos");
+        let (handle, name) = registry.route("fs__read_file").await.unwrap();
+        assert_eq!(handle.slug, "fs");
+        assert_eq!(name, "read_file");
+    }
+
+    #[tokio::test]
+    async fn test_flat_names_collision_is_withheld_and_refused() {
+        let (mut a, _a_tx) = mock_server_handle("a", HealthState::Healthy);
+        a.flat_names = true;
+        a.tools.store(Arc::new(vec![tool("search"), tool("fetch")]));
+        let (mut b, _b_tx) = mock_server_handle("b", HealthState::Healthy);
+        b.flat_names = true;
+        b.tools.store(Arc::new(vec![tool("search")]));
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), a);
+        servers.insert("b".to_string(), b);
+        let registry = PorterRegistry::from_handles(servers);
+
+        let names: Vec<String> = registry
+            .tools()
+            .await
+            .into_iter()
+            .map(|t| t.name.to_string())
+            .collect();
+        assert_eq!(names, vec!["fetch"]);
+
+        let err = registry.call_tool("search", None, None).await.unwrap_err();
+        assert!(err.to_string().contains("multiple flat-named servers"));
+    }
+
+    #[tokio::test]
+    async fn test_no_dedup_by_default() {
+        let (a, _a_tx) = mock_server_handle("a", HealthState::Healthy);
+        a.tools.store(Arc::new(vec![tool("a__search")]));
+        let (b, _b_tx) = mock_server_handle("b", HealthState::Healthy);
+        b.tools.store(Arc::new(vec![tool("b__search")]));
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), a);
+        servers.insert("b".to_string(), b);
+        let registry = PorterRegistry::from_handles(servers);
+        assert_eq!(registry.tools().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_forwards_meta() {
+        let (health_tx, health_rx) = tokio::sync::watch::channel(HealthState::Healthy);
+        let (call_tx, mut call_rx) = tokio::sync::mpsc::channel(1);
+        let handle = ServerHandle {
+            slug: "gh".to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(vec![])),
+            call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        let mut servers = HashMap::new();
+        servers.insert("gh".to_string(), handle);
+        let registry = PorterRegistry::from_handles(servers);
+
+        // Mock peer: capture the forwarded params and reply with an empty result
+        let peer = tokio::spawn(async move {
+            let req: ToolCallRequest = call_rx.recv().await.expect("request forwarded");
+            let _ = req.response_tx.send(Ok(CallToolResult::success(vec![])));
+            req.params
+        });
+
+        let mut meta = Meta::new();
+        meta.0
+            .insert("traceparent".to_string(), serde_json::json!("00-abc-01"));
+        registry
+            .call_tool("gh__list_repos", None, Some(meta.clone()))
+            .await
+            .unwrap();
+
+        let params = peer.await.unwrap();
+        assert_eq!(params.name, "list_repos");
+        assert_eq!(params.meta, Some(meta));
+        drop(health_tx);
+    }
+
     #[tokio::test]
     async fn test_call_tool_no_namespace() {
         let mut servers = HashMap::new();
         let (handle, _health_tx) = mock_server_handle("gh", HealthState::Healthy);
         servers.insert("gh".to_string(), handle);
-        let registry = PorterRegistry {
-            servers,
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(servers);
 
-        let result = registry.call_tool("list_repos", None).await;
+        let result = registry.call_tool("list_repos", None, None).await;
         assert!(
             matches!(result, Err(PorterError::Protocol(slug, msg)) if slug == "unknown" && msg.contains("no namespace prefix")),
             "Expected Protocol error for missing namespace"
@@ -286,12 +1110,9 @@ mod tests {
 
     #[tokio::test]
     async fn test_call_tool_unknown_slug() {
-        let registry = PorterRegistry {
-            servers: HashMap::new(),
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(HashMap::new());
 
-        let result = registry.call_tool("gh__list_repos", None).await;
+        let result = registry.call_tool("gh__list_repos", None, None).await;
         assert!(
             matches!(result, Err(PorterError::Protocol(slug, msg)) if slug == "gh" && msg.contains("no server with slug")),
             "Expected Protocol error for unknown slug"
@@ -303,12 +1124,9 @@ mod tests {
         let mut servers = HashMap::new();
         let (handle, _health_tx) = mock_server_handle("broken", HealthState::Unhealthy);
         servers.insert("broken".to_string(), handle);
-        let registry = PorterRegistry {
-            servers,
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(servers);
 
-        let result = registry.call_tool("broken__some_tool", None).await;
+        let result = registry.call_tool("broken__some_tool", None, None).await;
         assert!(
             matches!(result, Err(PorterError::ServerUnhealthy(slug, _)) if slug == "broken"),
             "Expected ServerUnhealthy error"
@@ -317,22 +1135,104 @@ mod tests {
 
     #[test]
     fn test_server_health_returns_none_for_unknown() {
-        let registry = PorterRegistry {
-            servers: HashMap::new(),
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(HashMap::new());
         assert!(registry.server_health("nonexistent").is_none());
     }
 
     #[test]
     fn test_all_server_health_empty() {
-        let registry = PorterRegistry {
-            servers: HashMap::new(),
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(HashMap::new());
         assert!(registry.all_server_health().is_empty());
     }
 
+    #[test]
+    fn test_is_ready_empty_registry() {
+        let registry = PorterRegistry::from_handles(HashMap::new());
+        assert!(registry.is_ready());
+    }
+
+    #[test]
+    fn test_is_ready_requires_all_servers_started() {
+        let mut servers = HashMap::new();
+        let (h1, _tx1) = mock_server_handle("a", HealthState::Healthy);
+        servers.insert("a".to_string(), h1);
+        let (h2, tx2) = mock_server_handle("b", HealthState::Starting);
+        servers.insert("b".to_string(), h2);
+        let registry = PorterRegistry::from_handles(servers);
+        assert!(!registry.is_ready());
+
+        tx2.send(HealthState::Degraded).unwrap();
+        assert!(registry.is_ready());
+
+        tx2.send(HealthState::Unhealthy).unwrap();
+        assert!(!registry.is_ready());
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_ready() {
+        let mut servers = HashMap::new();
+        let (h, tx) = mock_server_handle("a", HealthState::Starting);
+        servers.insert("a".to_string(), h);
+        let registry = PorterRegistry::from_handles(servers);
+        assert!(
+            !registry
+                .wait_until_ready(std::time::Duration::from_millis(150))
+                .await
+        );
+
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            tx.send(HealthState::Healthy).unwrap();
+        });
+        assert!(
+            registry
+                .wait_until_ready(std::time::Duration::from_secs(5))
+                .await
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_startup_reports_unhealthy_and_timeouts() {
+        let mut servers = HashMap::new();
+        let (a, _a_tx) = mock_server_handle("a", HealthState::Healthy);
+        servers.insert("a".to_string(), a);
+        let (b, b_tx) = mock_server_handle("b", HealthState::Starting);
+        servers.insert("b".to_string(), b);
+        let registry = PorterRegistry::from_handles(servers);
+
+        let err = registry
+            .check_startup(std::time::Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, PorterError::InitializationFailed(slug, _) if slug == "b"));
+
+        b_tx.send(HealthState::Healthy).unwrap();
+        registry
+            .check_startup(std::time::Duration::from_millis(50))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_check_startup_bad_stdio_command() {
+        let mut config = make_config(vec![]);
+        let mut server = stdio_config("bad", true);
+        server.command = Some("/nonexistent/porter-test-mcp-server".to_string());
+        config.servers.insert("bad".to_string(), server);
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+
+        let err = registry
+            .check_startup(std::time::Duration::from_secs(10))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, PorterError::InitializationFailed(slug, msg) if slug == "bad" && msg.contains("Degraded")),
+            "unexpected error: {}",
+            err
+        );
+        registry.shutdown().await;
+    }
+
     #[test]
     fn test_server_slugs_sorted() {
         let mut servers = HashMap::new();
@@ -342,10 +1242,7 @@ mod tests {
         servers.insert("alpha".to_string(), h2);
         let (h3, _tx3) = mock_server_handle("mango", HealthState::Healthy);
         servers.insert("mango".to_string(), h3);
-        let registry = PorterRegistry {
-            servers,
-            cancel: CancellationToken::new(),
-        };
+        let registry = PorterRegistry::from_handles(servers);
         assert_eq!(
             registry.server_slugs(),
             vec![
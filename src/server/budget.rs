@@ -0,0 +1,144 @@
+//! Registry-wide spawn budget for STDIO servers.
+//!
+//! Each STDIO run loop retries independently, so a correlated outage (e.g. a
+//! broken shared dependency) can make every server respawn at once. The
+//! `SpawnBudget` is shared by all run loops in a registry and caps the total
+//! number of process spawns within a sliding window.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+/// Window over which spawn attempts are counted.
+pub const SPAWN_BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// Shared sliding-window limiter on process spawns.
+pub struct SpawnBudget {
+    max_spawns: Option<usize>,
+    window: Duration,
+    spawns: Mutex<VecDeque<Instant>>,
+}
+
+impl SpawnBudget {
+    /// Create a budget allowing `max_spawns` spawns per `window` (`None` = unlimited).
+    pub fn new(max_spawns: Option<u32>, window: Duration) -> Self {
+        Self {
+            max_spawns: max_spawns.map(|m| m as usize),
+            window,
+            spawns: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// A budget that never limits spawns.
+    pub fn unlimited() -> Self {
+        Self::new(None, SPAWN_BUDGET_WINDOW)
+    }
+
+    /// A budget with a new `max_spawns` limit that keeps this budget's spawn
+    /// history, so a hot reload cannot reset the allowance.
+    pub(crate) async fn carried_over(&self, max_spawns: Option<u32>) -> Self {
+        Self {
+            max_spawns: max_spawns.map(|m| m as usize),
+            window: self.window,
+            spawns: Mutex::new(self.spawns.lock().await.clone()),
+        }
+    }
+
+    /// Reserve a spawn slot, or return how long until the oldest slot frees up.
+    pub(crate) async fn try_acquire(&self) -> Result<(), Duration> {
+        let Some(max) = self.max_spawns else {
+            return Ok(());
+        };
+        let now = Instant::now();
+        let mut spawns = self.spawns.lock().await;
+        while let Some(ts) = spawns.front() {
+            if now.duration_since(*ts) >= self.window {
+                spawns.pop_front();
+            } else {
+                break;
+            }
+        }
+        if spawns.len() < max {
+            spawns.push_back(now);
+            return Ok(());
+        }
+        let oldest = spawns.front().copied().unwrap_or(now);
+        Err(self.window.saturating_sub(now.duration_since(oldest)))
+    }
+
+    /// Wait until a spawn slot is available and reserve it.
+    pub(crate) async fn acquire(&self) {
+        while let Err(wait) = self.try_acquire().await {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_unlimited_never_blocks() {
+        let budget = SpawnBudget::unlimited();
+        for _ in 0..100 {
+            assert!(budget.try_acquire().await.is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_budget_caps_spawns_in_window() {
+        let budget = SpawnBudget::new(Some(3), Duration::from_secs(60));
+        for _ in 0..3 {
+            assert!(budget.try_acquire().await.is_ok());
+        }
+        let wait = budget.try_acquire().await.unwrap_err();
+        assert!(wait <= Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_budget_frees_after_window() {
+        let budget = SpawnBudget::new(Some(1), Duration::from_millis(50));
+        assert!(budget.try_acquire().await.is_ok());
+        assert!(budget.try_acquire().await.is_err());
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(budget.try_acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_carried_over_budget_keeps_spawn_history() {
+        let budget = SpawnBudget::new(Some(2), Duration::from_secs(60));
+        for _ in 0..2 {
+            assert!(budget.try_acquire().await.is_ok());
+        }
+        let raised = budget.carried_over(Some(3)).await;
+        assert!(raised.try_acquire().await.is_ok());
+        assert!(raised.try_acquire().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_shared_budget_caps_aggregate_spawns() {
+        // Ten "servers" share a budget of 4 spawns per window; only 4 get
+        // through before the window elapses.
+        let budget = Arc::new(SpawnBudget::new(Some(4), Duration::from_millis(500)));
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let mut tasks = Vec::new();
+        for _ in 0..10 {
+            let budget = budget.clone();
+            let spawned = spawned.clone();
+            tasks.push(tokio::spawn(async move {
+                budget.acquire().await;
+                spawned.fetch_add(1, Ordering::SeqCst);
+            }));
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(spawned.load(Ordering::SeqCst), 4);
+        for task in tasks {
+            task.abort();
+        }
+    }
+}
@@ -7,9 +7,10 @@ use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 /// Four-state health model for managed MCP servers.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum HealthState {
-    /// Insufficient samples to determine health (fewer than 5)
+    /// Not connected yet; `ErrorRateTracker` also reports it with fewer than 5 samples
     Starting,
     /// Error rate below 5%
     Healthy,
@@ -48,6 +49,15 @@ impl ErrorRateTracker {
         self.prune();
     }
 
+    /// Fraction of calls in the window that failed, in `0.0..=1.0` (0.0 when empty).
+    pub fn error_rate(&self) -> f64 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        let errors = self.window.iter().filter(|(_, is_err)| *is_err).count();
+        errors as f64 / self.window.len() as f64
+    }
+
     /// Compute current health state based on the sliding window.
     pub fn health_state(&self) -> HealthState {
         let total = self.window.len();
@@ -57,8 +67,7 @@ impl ErrorRateTracker {
             return HealthState::Starting;
         }
 
-        let errors = self.window.iter().filter(|(_, is_err)| *is_err).count();
-        let error_rate = errors as f64 / total as f64;
+        let error_rate = self.error_rate();
 
         if error_rate < 0.05 {
             HealthState::Healthy
@@ -80,12 +89,6 @@ impl ErrorRateTracker {
             }
         }
     }
-
-    /// Number of entries currently in the window (for testing).
-    #[cfg(test)]
-    pub fn len(&self) -> usize {
-        self.window.len()
-    }
 }
 
 /// Rolling buffer for per-server stderr output, for diagnostics.
@@ -132,6 +135,16 @@ mod tests {
         tracker
     }
 
+    #[test]
+    fn test_error_rate() {
+        assert_eq!(
+            ErrorRateTracker::new(Duration::from_secs(60)).error_rate(),
+            0.0
+        );
+        let tracker = tracker_with_samples(3, 1);
+        assert!((tracker.error_rate() - 0.25).abs() < f64::EPSILON);
+    }
+
     #[test]
     fn test_health_starting_below_threshold() {
         let tracker = tracker_with_samples(4, 0);
@@ -184,12 +197,12 @@ mod tests {
         tracker.record_success();
         tracker.record_success();
         tracker.record_error();
-        assert_eq!(tracker.len(), 3);
+        assert_eq!(tracker.window.len(), 3);
         // Wait for window to expire
         std::thread::sleep(Duration::from_millis(100));
         // Adding a new entry will trigger prune of old entries
         tracker.record_success();
         // The 3 old entries should have been pruned
-        assert_eq!(tracker.len(), 1);
+        assert_eq!(tracker.window.len(), 1);
     }
 }
@@ -5,59 +5,82 @@
 //! filtering. The server manager connects, performs the MCP handshake, lists tools,
 //! and forwards tool calls.
 //!
-//! A reconnect loop with exponential backoff handles connection failures (1s → 30s cap).
+//! Servers configured with `transport = "sse"` share this run loop but connect
+//! through the legacy SSE client in `server::sse`.
+//!
+//! A reconnect loop with exponential backoff handles connection failures (1s → 30s cap);
+//! with `immediate_first_retry` the first failure is retried without waiting.
 
 use std::sync::Arc;
 use std::time::Duration;
 
-use rmcp::model::Tool;
 use rmcp::service::RunningService;
 use rmcp::transport::StreamableHttpClientTransport;
 use rmcp::{RoleClient, ServiceExt};
-use tokio::sync::{RwLock, mpsc, watch};
+use tokio::sync::{Notify, broadcast, mpsc, watch};
 use tokio_util::sync::CancellationToken;
 
-use crate::config::ServerConfig;
+use crate::config::{ServerConfig, TransportKind};
 use crate::error::PorterError;
-use crate::namespace::namespace_tool;
 use crate::server::health::{ErrorRateTracker, HealthState};
-use crate::server::{BACKOFF_INITIAL, BACKOFF_MAX, MAX_FAILURES, ServerHandle, ToolCallRequest};
+use crate::server::sse::SseClientTransport;
+use crate::server::{
+    BACKOFF_INITIAL, Dependency, MAX_FAILURES, PorterClient, ServerHandle, ServerShared,
+    ToolCallRequest, check_protocol_version, discover_tools, forward_call, health_probe_ticker,
+    next_retry_delay, refresh_tools, run_health_probe, wait_for_dependencies,
+};
 
-/// Connect to a remote Streamable HTTP MCP server and perform the handshake.
+/// Connect to a remote HTTP MCP server and perform the handshake.
 ///
-/// Constructs the transport from the server URL and performs the MCP handshake
-/// with a configurable timeout.
+/// Constructs a Streamable HTTP or legacy SSE transport (per `config.transport`)
+/// from the server URL and performs the MCP handshake with a configurable timeout.
 async fn connect_and_handshake(
     config: &ServerConfig,
     slug: &str,
+    list_changed: Arc<Notify>,
     cancel: CancellationToken,
-) -> crate::Result<RunningService<RoleClient, ()>> {
+) -> crate::Result<RunningService<RoleClient, PorterClient>> {
     let url = config.url.as_ref().ok_or_else(|| {
         PorterError::InvalidConfig(
             slug.to_string(),
-            "HTTP transport requires 'url' field".to_string(),
+            format!(
+                "{} transport requires 'url' field",
+                config.transport.label()
+            ),
         )
     })?;
 
-    let transport = StreamableHttpClientTransport::from_uri(url.as_str());
+    let timeout = Duration::from_secs(config.handshake_timeout_secs);
+    let client = PorterClient::new(config, list_changed);
+    let handshake_result =
+        match config.transport {
+            TransportKind::Sse => {
+                tokio::time::timeout(timeout, async {
+                    let transport = SseClientTransport::connect(url, slug).await?;
+                    client.serve_with_ct(transport, cancel).await.map_err(|e| {
+                        PorterError::InitializationFailed(slug.to_string(), e.to_string())
+                    })
+                })
+                .await
+            }
+            _ => {
+                let transport = StreamableHttpClientTransport::from_uri(url.as_str());
+                tokio::time::timeout(timeout, async {
+                    client.serve_with_ct(transport, cancel).await.map_err(|e| {
+                        PorterError::InitializationFailed(slug.to_string(), e.to_string())
+                    })
+                })
+                .await
+            }
+        };
 
     let timeout_secs = config.handshake_timeout_secs;
-    let handshake_result = tokio::time::timeout(
-        Duration::from_secs(timeout_secs),
-        ().serve_with_ct(transport, cancel),
-    )
-    .await;
-
     match handshake_result {
         Err(_elapsed) => Err(PorterError::InitializationFailed(
             slug.to_string(),
             format!("HTTP MCP handshake timed out after {}s", timeout_secs),
         )),
-        Ok(Err(e)) => Err(PorterError::InitializationFailed(
-            slug.to_string(),
-            e.to_string(),
-        )),
-        Ok(Ok(running)) => Ok(running),
+        Ok(result) => result,
     }
 }
 
@@ -65,14 +88,26 @@ async fn connect_and_handshake(
 ///
 /// Runs in a `tokio::spawn` task. Handles connection, handshake, tool discovery,
 /// call forwarding, reconnect with exponential backoff, and clean shutdown.
+/// No connection is attempted until every dependency is Healthy.
 pub(crate) async fn run_http_server(
     config: ServerConfig,
     slug: String,
-    tools: Arc<RwLock<Vec<Tool>>>,
     call_rx: mpsc::Receiver<ToolCallRequest>,
     health_tx: watch::Sender<HealthState>,
+    shared: ServerShared,
     cancel: CancellationToken,
 ) {
+    let ServerShared {
+        tools,
+        status,
+        tools_changed,
+        list_changed,
+        dependencies,
+    } = shared;
+    if !wait_for_dependencies(&slug, dependencies, &cancel).await {
+        return;
+    }
+
     let call_rx = Arc::new(tokio::sync::Mutex::new(call_rx));
 
     let mut consecutive_failures: u32 = 0;
@@ -84,9 +119,14 @@ pub(crate) async fn run_http_server(
 
         tracing::info!(server = %slug, "connecting to HTTP MCP server");
 
-        match connect_and_handshake(&config, &slug, cancel.clone()).await {
+        match connect_and_handshake(&config, &slug, list_changed.clone(), cancel.clone()).await {
             Err(e) => {
                 tracing::warn!(server = %slug, error = %e, "HTTP server connect/handshake failed");
+                if !e.is_retryable() {
+                    tracing::error!(server = %slug, error = %e, "non-retryable connect error — marking Unhealthy");
+                    let _ = health_tx.send(HealthState::Unhealthy);
+                    return;
+                }
                 consecutive_failures += 1;
                 if consecutive_failures >= MAX_FAILURES {
                     tracing::error!(
@@ -98,44 +138,33 @@ pub(crate) async fn run_http_server(
                     return;
                 }
                 let _ = health_tx.send(HealthState::Degraded);
+                let delay = next_retry_delay(&config, consecutive_failures, &mut backoff);
                 tracing::info!(
                     server = %slug,
-                    backoff_secs = backoff.as_secs(),
+                    backoff_ms = delay.as_millis() as u64,
                     "backing off before reconnect"
                 );
                 tokio::select! {
-                    _ = tokio::time::sleep(backoff) => {}
+                    _ = tokio::time::sleep(delay) => {}
                     _ = cancel.cancelled() => {
                         tracing::info!(server = %slug, "cancelled during backoff sleep");
                         return;
                     }
                 }
-                backoff = std::cmp::min(backoff * 2, BACKOFF_MAX);
                 continue;
             }
             Ok(running) => {
                 // --- Tool discovery ---
                 let peer = running.peer().clone();
 
-                match peer.list_all_tools().await {
-                    Ok(discovered_tools) => {
-                        let namespaced: Vec<Tool> = discovered_tools
-                            .into_iter()
-                            .map(|t| namespace_tool(&slug, t))
-                            .collect();
-                        let count = namespaced.len();
-                        *tools.write().await = namespaced;
-                        tracing::info!(server = %slug, tool_count = count, "HTTP tools discovered");
-                    }
-                    Err(e) => {
-                        tracing::warn!(server = %slug, error = %e, "failed to list tools after HTTP handshake");
-                    }
-                }
+                status.record_listing(discover_tools(&peer, &slug, &config, &tools).await);
+                check_protocol_version(&peer, &slug, &config, &status);
 
                 // Reset failure counters on successful connection
                 consecutive_failures = 0;
                 backoff = BACKOFF_INITIAL;
-                let _ = health_tx.send(HealthState::Healthy);
+                let mut error_tracker = ErrorRateTracker::new(Duration::from_secs(60));
+                let _ = health_tx.send(status.record(&error_tracker));
 
                 // --- Spawn a task to watch for session termination ---
                 let (exit_tx, mut exit_rx) = tokio::sync::oneshot::channel::<()>();
@@ -145,7 +174,8 @@ pub(crate) async fn run_http_server(
                 });
 
                 // --- Call-forwarding + exit detection loop ---
-                let mut error_tracker = ErrorRateTracker::new(Duration::from_secs(60));
+                let mut probe_ticker = health_probe_ticker(&config);
+                let probe_timeout = Duration::from_secs(config.health_check_interval_secs.max(1));
                 let exited_unexpectedly = loop {
                     let mut rx_guard = call_rx.lock().await;
                     tokio::select! {
@@ -158,18 +188,28 @@ pub(crate) async fn run_http_server(
                                     return;
                                 }
                                 Some(req) => {
-                                    let result = peer.call_tool(req.params).await
-                                        .map_err(|e| PorterError::Protocol(slug.clone(), e.to_string()));
+                                    let result = forward_call(&peer, &slug, &config, req.params).await;
                                     match &result {
                                         Ok(_) => error_tracker.record_success(),
                                         Err(_) => error_tracker.record_error(),
                                     }
-                                    let new_health = error_tracker.health_state();
-                                    let _ = health_tx.send(new_health);
+                                    let _ = health_tx.send(status.record(&error_tracker));
                                     let _ = req.response_tx.send(result);
                                 }
                             }
                         }
+                        _ = list_changed.notified() => {
+                            drop(rx_guard);
+                            status.record_listing(refresh_tools(&peer, &slug, &config, &tools, &tools_changed).await);
+                            let _ = health_tx.send(status.record(&error_tracker));
+                        }
+                        _ = probe_ticker.tick(), if config.health_check_tool.is_some() => {
+                            drop(rx_guard);
+                            if let Some(tool) = &config.health_check_tool {
+                                run_health_probe(&peer, &slug, tool, probe_timeout, &mut error_tracker).await;
+                                let _ = health_tx.send(status.record(&error_tracker));
+                            }
+                        }
                         _ = &mut exit_rx => {
                             // Session terminated unexpectedly — reconnect
                             drop(rx_guard);
@@ -186,7 +226,7 @@ pub(crate) async fn run_http_server(
                 if exited_unexpectedly {
                     tracing::warn!(server = %slug, "HTTP session terminated unexpectedly, reconnecting");
                     // Clear tools since connection is down
-                    tools.write().await.clear();
+                    tools.store(Arc::new(Vec::new()));
                     consecutive_failures += 1;
                     if consecutive_failures >= MAX_FAILURES {
                         tracing::error!(
@@ -198,19 +238,19 @@ pub(crate) async fn run_http_server(
                         return;
                     }
                     let _ = health_tx.send(HealthState::Degraded);
+                    let delay = next_retry_delay(&config, consecutive_failures, &mut backoff);
                     tracing::info!(
                         server = %slug,
-                        backoff_secs = backoff.as_secs(),
+                        backoff_ms = delay.as_millis() as u64,
                         "backing off before reconnect"
                     );
                     tokio::select! {
-                        _ = tokio::time::sleep(backoff) => {}
+                        _ = tokio::time::sleep(delay) => {}
                         _ = cancel.cancelled() => {
                             tracing::info!(server = %slug, "cancelled during backoff sleep");
                             return;
                         }
                     }
-                    backoff = std::cmp::min(backoff * 2, BACKOFF_MAX);
                 }
             }
         }
@@ -221,26 +261,30 @@ pub(crate) async fn run_http_server(
 ///
 /// This is the primary entry point for Porter to start managing an external
 /// HTTP MCP server. The returned `ServerHandle` provides health monitoring,
-/// tool listing, and tool call routing.
+/// tool listing, and tool call routing. The slug is sent on `tools_changed`
+/// whenever the server's tool list is re-listed. No connection is attempted
+/// until every one of `dependencies` is Healthy.
 pub fn spawn_http_server(
     config: ServerConfig,
     slug: String,
+    tools_changed: broadcast::Sender<String>,
+    dependencies: Vec<Dependency>,
     cancel: CancellationToken,
 ) -> ServerHandle {
     let (health_tx, health_rx) = watch::channel(HealthState::Starting);
     let (call_tx, call_rx) = mpsc::channel(32);
-    let tools = Arc::new(RwLock::new(Vec::<Tool>::new()));
-
-    let tools_clone = tools.clone();
-    let slug_clone = slug.clone();
+    let shared = ServerShared::new(tools_changed, dependencies);
+    let tools = shared.tools.clone();
+    let status = shared.status.clone();
+    let flat_names = config.flat_names;
 
     tokio::spawn(run_http_server(
         config,
-        slug_clone,
-        tools_clone,
+        slug.clone(),
         call_rx,
         health_tx,
-        cancel,
+        shared,
+        cancel.clone(),
     ));
 
     ServerHandle {
@@ -248,12 +292,49 @@ pub fn spawn_http_server(
         health_rx,
         tools,
         call_tx,
+        cancel,
+        flat_names,
+        status,
     }
 }
 
+/// Serve `server` over Streamable HTTP on a local port and return its URL,
+/// for tests that run a real HTTP server loop against an in-process backend.
+#[cfg(test)]
+pub(crate) async fn serve_mock_http<S>(server: S) -> String
+where
+    S: rmcp::ServerHandler + Clone + Send + 'static,
+{
+    use axum::response::IntoResponse;
+    use rmcp::transport::streamable_http_server::{
+        StreamableHttpServerConfig, StreamableHttpService, session::local::LocalSessionManager,
+    };
+    use tower::ServiceExt;
+
+    let service = StreamableHttpService::new(
+        move || Ok(server.clone()),
+        Arc::new(LocalSessionManager::default()),
+        StreamableHttpServerConfig::default(),
+    );
+    let app = axum::Router::new().fallback(move |req: axum::extract::Request| {
+        let service = service.clone();
+        async move {
+            match service.oneshot(req).await {
+                Ok(response) => response.into_response(),
+                Err(never) => match never {},
+            }
+        }
+    });
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await });
+    format!("http://{}/mcp", addr)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::server::BACKOFF_MAX;
 
     #[tokio::test]
     async fn test_http_transport_construction() {
@@ -272,6 +353,91 @@ mod tests {
         drop(transport);
     }
 
+    #[tokio::test]
+    async fn test_sse_connect_failure_is_reported() {
+        // Port 9 (discard) is not expected to serve SSE
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "legacy"
+            transport = "sse"
+            url = "http://127.0.0.1:9/sse"
+            handshake_timeout_secs = 5
+            "#,
+        )
+        .unwrap();
+        let result = connect_and_handshake(
+            &config,
+            "legacy",
+            Arc::new(Notify::new()),
+            CancellationToken::new(),
+        )
+        .await;
+        assert!(matches!(result, Err(PorterError::Transport(slug, _)) if slug == "legacy"));
+    }
+
+    /// Backend whose `tools/list` always fails but whose tool calls succeed.
+    #[derive(Clone)]
+    struct UnlistableServer;
+
+    impl rmcp::ServerHandler for UnlistableServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListToolsResult, rmcp::ErrorData> {
+            Err(rmcp::ErrorData::internal_error("listing broken", None))
+        }
+
+        async fn call_tool(
+            &self,
+            _request: rmcp::model::CallToolRequestParams,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
+            Ok(rmcp::model::CallToolResult::success(vec![
+                rmcp::model::Content::text("ok"),
+            ]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failed_listing_stays_degraded_after_calls() {
+        let url = serve_mock_http(UnlistableServer).await;
+        let mut config = crate::PorterConfig::default();
+        config.servers.insert(
+            "mock".to_string(),
+            toml::from_str(&format!(
+                "slug = \"mock\"\ntransport = \"http\"\nurl = \"{}\"",
+                url
+            ))
+            .unwrap(),
+        );
+        let registry = crate::PorterRegistry::from_config(config).await.unwrap();
+        assert!(registry.wait_until_ready(Duration::from_secs(10)).await);
+        assert_eq!(registry.server_health("mock"), Some(HealthState::Degraded));
+
+        // Enough successful calls to fill the error-rate window
+        for _ in 0..6 {
+            registry.call_tool("mock__echo", None, None).await.unwrap();
+        }
+        assert_eq!(registry.server_health("mock"), Some(HealthState::Degraded));
+        assert_eq!(
+            registry.server_snapshots()["mock"]
+                .degraded_reason
+                .as_deref(),
+            Some("tool listing failed")
+        );
+        registry.shutdown().await;
+    }
+
     #[test]
     fn test_backoff_cap_at_30s() {
         let mut backoff = BACKOFF_INITIAL;
@@ -4,16 +4,33 @@
 //! mod.rs declares all submodules upfront so Plans 02 and 03 only create
 //! new files without needing to modify this file.
 
+pub mod budget;
 pub mod health;
 pub mod http;
+pub mod sse;
 pub mod stdio;
 
-use rmcp::model::{CallToolRequestParams, CallToolResult, Tool};
+use arc_swap::ArcSwap;
+use rmcp::ClientHandler;
+use rmcp::model::{
+    CallToolRequest, CallToolRequestParams, CallToolResult, ClientInfo, ClientRequest,
+    RootsCapabilities, ServerResult, Tool,
+};
+use rmcp::service::{NotificationContext, Peer, PeerRequestOptions, RoleClient};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::time::Duration;
-use tokio::sync::{RwLock, mpsc, watch};
+use tokio::sync::{Notify, broadcast, mpsc, watch};
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
+use tracing::field::Empty;
 
-use crate::server::health::HealthState;
+use crate::config::ServerConfig;
+use crate::error::PorterError;
+use crate::namespace::{
+    attribute_tool, namespace_tool, override_input_schema, truncate_description,
+};
+use crate::server::health::{ErrorRateTracker, HealthState};
 
 /// Maximum consecutive failures before marking server Unhealthy.
 pub(crate) const MAX_FAILURES: u32 = 5;
@@ -24,6 +41,428 @@ pub(crate) const BACKOFF_INITIAL: Duration = Duration::from_secs(1);
 /// Maximum backoff duration cap.
 pub(crate) const BACKOFF_MAX: Duration = Duration::from_secs(30);
 
+/// Attempts at `tools/list` after a successful handshake before giving up.
+pub(crate) const LIST_TOOLS_ATTEMPTS: u32 = 3;
+
+/// Delay before the first `tools/list` retry; doubles on each further retry.
+pub(crate) const LIST_TOOLS_RETRY_DELAY: Duration = Duration::from_millis(250);
+
+/// A server that must be Healthy before a dependent starts: its slug and health.
+pub type Dependency = (String, watch::Receiver<HealthState>);
+
+/// Wait until every dependency reports Healthy (`depends_on`).
+///
+/// A dependency whose run loop has ended (permanently Unhealthy or shut down)
+/// no longer blocks: the dependent starts anyway with a warning. Returns
+/// `false` if `cancel` fires first.
+pub(crate) async fn wait_for_dependencies(
+    slug: &str,
+    dependencies: Vec<Dependency>,
+    cancel: &CancellationToken,
+) -> bool {
+    for (dependency, mut health) in dependencies {
+        if *health.borrow() == HealthState::Healthy {
+            continue;
+        }
+        tracing::info!(server = %slug, dependency = %dependency, "waiting for dependency to become Healthy");
+        tokio::select! {
+            result = health.wait_for(|h| *h == HealthState::Healthy) => {
+                if result.is_err() {
+                    tracing::warn!(
+                        server = %slug,
+                        dependency = %dependency,
+                        "dependency stopped before becoming Healthy, starting anyway"
+                    );
+                }
+            }
+            _ = cancel.cancelled() => return false,
+        }
+    }
+    true
+}
+
+/// Delay before the next restart/reconnect after `consecutive_failures` failures.
+///
+/// With `immediate_first_retry`, the first failure is retried at once and the
+/// backoff schedule starts at the second. Otherwise returns `backoff` and
+/// doubles it (capped at `BACKOFF_MAX`) for next time.
+pub(crate) fn next_retry_delay(
+    config: &ServerConfig,
+    consecutive_failures: u32,
+    backoff: &mut Duration,
+) -> Duration {
+    if config.immediate_first_retry && consecutive_failures == 1 {
+        return Duration::ZERO;
+    }
+    let delay = *backoff;
+    *backoff = std::cmp::min(*backoff * 2, BACKOFF_MAX);
+    delay
+}
+
+/// Pause before re-sending a tool call that failed transiently (`call_retries`).
+pub(crate) const CALL_RETRY_DELAY: Duration = Duration::from_millis(200);
+
+/// `_meta` key a client sets to bound a tool call, in milliseconds.
+pub const CLIENT_TIMEOUT_META_KEY: &str = "porter/timeoutMs";
+
+/// Prefix of the `_meta` keys Porter itself interprets (`porter/timeoutMs`,
+/// `porter/ifNoneMatch`); they are stripped before a call reaches a backend.
+pub const PORTER_META_PREFIX: &str = "porter/";
+
+/// Remove Porter's own `porter/*` keys from a call's `_meta`, dropping `_meta`
+/// entirely if nothing else is left.
+fn strip_porter_meta(mut params: CallToolRequestParams) -> CallToolRequestParams {
+    if let Some(meta) = params.meta.as_mut() {
+        meta.0.retain(|key, _| !key.starts_with(PORTER_META_PREFIX));
+        if meta.0.is_empty() {
+            params.meta = None;
+        }
+    }
+    params
+}
+
+/// Effective timeout for a call: the shorter of the server's `call_timeout_secs`
+/// and the client's `_meta` deadline, if either is set.
+pub(crate) fn call_timeout(
+    config: &ServerConfig,
+    params: &CallToolRequestParams,
+) -> Option<Duration> {
+    let configured = config.call_timeout_secs.map(Duration::from_secs);
+    let client = params
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.0.get(CLIENT_TIMEOUT_META_KEY))
+        .and_then(serde_json::Value::as_u64)
+        .map(Duration::from_millis);
+    match (configured, client) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Map a failed call to a `PorterError`, so `PorterError::is_retryable` decides
+/// whether to re-send it: a request that could not be written is `Transport`
+/// and a JSON-RPC internal error is `Internal`, both retryable.
+///
+/// Everything else is a non-retryable `Protocol` error: timeouts (the deadline
+/// is spent), closed transports (the run loop reconnects) and errors about the
+/// request itself.
+fn call_error(slug: &str, error: rmcp::ServiceError) -> PorterError {
+    match &error {
+        rmcp::ServiceError::TransportSend(_) => {
+            PorterError::Transport(slug.to_string(), error.to_string())
+        }
+        rmcp::ServiceError::McpError(data)
+            if data.code == rmcp::model::ErrorCode::INTERNAL_ERROR =>
+        {
+            PorterError::Internal(slug.to_string(), error.to_string())
+        }
+        _ => PorterError::Protocol(slug.to_string(), error.to_string()),
+    }
+}
+
+/// Forward a tool call to a server, bounded by `call_timeout`.
+///
+/// Transient failures are re-sent up to `call_retries` times, within the same
+/// overall deadline. Porter's `porter/*` `_meta` keys are read here and never
+/// forwarded. On timeout rmcp sends the server `notifications/cancelled`
+/// so it can stop the work, and the run loop moves on to the next call. The
+/// whole exchange runs in a `porter.backend_call` span.
+pub(crate) async fn forward_call(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    config: &ServerConfig,
+    params: CallToolRequestParams,
+) -> crate::Result<CallToolResult> {
+    let span = tracing::info_span!(
+        "porter.backend_call",
+        server = %slug,
+        tool = %params.name,
+        outcome = Empty,
+        duration_ms = Empty,
+    );
+    let started = tokio::time::Instant::now();
+    let result = send_with_retries(peer, slug, config, params)
+        .instrument(span.clone())
+        .await;
+    span.record("outcome", call_outcome(&result));
+    span.record("duration_ms", started.elapsed().as_millis() as u64);
+    result
+}
+
+/// Span `outcome` for a tool call: `ok`, `tool_error` (the tool reported
+/// `isError`), or `error` (the call itself failed).
+pub(crate) fn call_outcome<E>(result: &Result<CallToolResult, E>) -> &'static str {
+    match result {
+        Ok(r) if r.is_error == Some(true) => "tool_error",
+        Ok(_) => "ok",
+        Err(_) => "error",
+    }
+}
+
+/// `forward_call` without its span: one deadline, retrying transient failures.
+async fn send_with_retries(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    config: &ServerConfig,
+    params: CallToolRequestParams,
+) -> crate::Result<CallToolResult> {
+    let deadline = call_timeout(config, &params).map(|t| tokio::time::Instant::now() + t);
+    let params = strip_porter_meta(params);
+    let mut attempt = 0;
+    loop {
+        let timeout = deadline.map(|d| d.saturating_duration_since(tokio::time::Instant::now()));
+        let error = match send_call(peer, params.clone(), timeout).await {
+            Ok(result) => return Ok(result),
+            Err(e) => call_error(slug, e),
+        };
+        let time_left = timeout.is_none_or(|t| t > CALL_RETRY_DELAY);
+        if attempt >= config.call_retries || !error.is_retryable() || !time_left {
+            return Err(error);
+        }
+        attempt += 1;
+        tracing::warn!(server = %slug, attempt, error = %error, "transient tool call failure, retrying");
+        tokio::time::sleep(CALL_RETRY_DELAY).await;
+    }
+}
+
+/// Send one `tools/call` and wait for its result.
+///
+/// The call's `_meta` travels as request meta: rmcp always sets its own
+/// `_meta` (the progress token) on the request, and leaving a second one on
+/// the params would serialize a duplicate key the backend rejects.
+async fn send_call(
+    peer: &Peer<RoleClient>,
+    mut params: CallToolRequestParams,
+    timeout: Option<Duration>,
+) -> Result<CallToolResult, rmcp::ServiceError> {
+    let options = PeerRequestOptions {
+        timeout,
+        meta: params.meta.take(),
+    };
+    let request = ClientRequest::CallToolRequest(CallToolRequest {
+        method: Default::default(),
+        params,
+        extensions: Default::default(),
+    });
+    let response = peer
+        .send_request_with_option(request, options)
+        .await?
+        .await_response()
+        .await?;
+    match response {
+        ServerResult::CallToolResult(result) => Ok(result),
+        _ => Err(rmcp::ServiceError::UnexpectedResponse),
+    }
+}
+
+/// Discover a freshly connected server's tools, retrying briefly on failure.
+///
+/// On success the prepared tools are stored and `Healthy` is returned. If every
+/// attempt fails the tool list is left untouched and `Degraded` is returned, so a
+/// server that answers the handshake but cannot list tools is not reported Healthy.
+pub(crate) async fn discover_tools(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    config: &ServerConfig,
+    tools: &ArcSwap<Vec<Tool>>,
+) -> HealthState {
+    let mut delay = LIST_TOOLS_RETRY_DELAY;
+    for attempt in 1..=LIST_TOOLS_ATTEMPTS {
+        match peer.list_all_tools().await {
+            Ok(discovered_tools) => {
+                let prepared = prepare_tools(slug, config, discovered_tools);
+                let count = prepared.len();
+                tools.store(Arc::new(prepared));
+                tracing::info!(server = %slug, tool_count = count, "tools discovered");
+                return HealthState::Healthy;
+            }
+            Err(e) => {
+                tracing::warn!(server = %slug, attempt, error = %e, "failed to list tools after handshake");
+                if attempt < LIST_TOOLS_ATTEMPTS {
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+            }
+        }
+    }
+    tracing::warn!(server = %slug, "tool listing kept failing — marking Degraded");
+    HealthState::Degraded
+}
+
+/// Record the protocol version a freshly connected server negotiated in `status`.
+///
+/// A version older than the server's `min_protocol_version` holds the
+/// connection at Degraded (see `ServerStatus::record`), with the reason kept
+/// for the health snapshot.
+pub(crate) fn check_protocol_version(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    config: &ServerConfig,
+    status: &ServerStatus,
+) {
+    let negotiated = peer
+        .peer_info()
+        .map(|info| info.protocol_version.to_string());
+    tracing::info!(server = %slug, protocol_version = ?negotiated, "negotiated MCP protocol version");
+
+    let issue = match (&negotiated, &config.min_protocol_version) {
+        // Protocol versions are YYYY-MM-DD dates, so string order is release order
+        (Some(version), Some(minimum)) if version < minimum => {
+            tracing::warn!(
+                server = %slug,
+                protocol_version = %version,
+                min_protocol_version = %minimum,
+                "server negotiated a protocol version older than min_protocol_version — marking Degraded"
+            );
+            Some(format!(
+                "protocol version {} is older than min_protocol_version {}",
+                version, minimum
+            ))
+        }
+        _ => None,
+    };
+    *status
+        .protocol_version
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = negotiated;
+    *status
+        .protocol_issue
+        .write()
+        .unwrap_or_else(|e| e.into_inner()) = issue;
+}
+
+/// Ticker for a server's `health_check_tool` probes; the first tick fires one
+/// interval from now rather than immediately after discovery.
+pub(crate) fn health_probe_ticker(config: &ServerConfig) -> tokio::time::Interval {
+    let period = Duration::from_secs(config.health_check_interval_secs.max(1));
+    let mut ticker = tokio::time::interval_at(tokio::time::Instant::now() + period, period);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    ticker
+}
+
+/// Call a server's health-check tool once and record the outcome in `tracker`.
+///
+/// A transport error, an `is_error` result, or no reply within `timeout` counts
+/// as a failed probe. Returns the tracker's resulting health state.
+pub(crate) async fn run_health_probe(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    tool: &str,
+    timeout: Duration,
+    tracker: &mut ErrorRateTracker,
+) -> HealthState {
+    let params = CallToolRequestParams {
+        name: tool.to_string().into(),
+        arguments: None,
+        task: None,
+        meta: None,
+    };
+    match tokio::time::timeout(timeout, peer.call_tool(params)).await {
+        Ok(Ok(result)) if !result.is_error.unwrap_or(false) => tracker.record_success(),
+        Ok(Ok(_)) => {
+            tracing::warn!(server = %slug, tool = %tool, "health probe returned an error result");
+            tracker.record_error();
+        }
+        Ok(Err(e)) => {
+            tracing::warn!(server = %slug, tool = %tool, error = %e, "health probe failed");
+            tracker.record_error();
+        }
+        Err(_) => {
+            tracing::warn!(server = %slug, tool = %tool, "health probe timed out");
+            tracker.record_error();
+        }
+    }
+    tracker.health_state()
+}
+
+/// Apply a server's schema overrides, namespace its discovered tools (unless
+/// `flat_names`), and apply its description size cap.
+pub(crate) fn prepare_tools(slug: &str, config: &ServerConfig, discovered: Vec<Tool>) -> Vec<Tool> {
+    discovered
+        .into_iter()
+        .map(|t| match config.schema_overrides.get(t.name.as_ref()) {
+            Some(serde_json::Value::Object(overlay)) => override_input_schema(t, overlay),
+            _ => t,
+        })
+        .map(|t| {
+            if config.flat_names {
+                attribute_tool(slug, t)
+            } else {
+                namespace_tool(slug, t)
+            }
+        })
+        .map(|t| match config.max_description_len {
+            Some(max_len) => truncate_description(t, max_len),
+            None => t,
+        })
+        .collect()
+}
+
+/// Build the `initialize` client info for a server from its config overrides.
+pub(crate) fn client_info(config: &ServerConfig) -> ClientInfo {
+    let mut info = ClientInfo::default();
+    if let Some(name) = &config.client_name {
+        info.client_info.name = name.clone();
+    }
+    if let Some(version) = &config.client_version {
+        info.client_info.version = version.clone();
+    }
+    if config.client_roots {
+        info.capabilities.roots = Some(RootsCapabilities::default());
+    }
+    info
+}
+
+/// Client-side handler Porter runs against each managed server.
+///
+/// Identifies itself with the configured client info and flags the server's
+/// `notifications/tools/list_changed` so the run loop can re-list tools.
+#[derive(Debug, Clone)]
+pub(crate) struct PorterClient {
+    info: ClientInfo,
+    tools_changed: Arc<Notify>,
+}
+
+impl PorterClient {
+    pub(crate) fn new(config: &ServerConfig, tools_changed: Arc<Notify>) -> Self {
+        Self {
+            info: client_info(config),
+            tools_changed,
+        }
+    }
+}
+
+impl ClientHandler for PorterClient {
+    fn get_info(&self) -> ClientInfo {
+        self.info.clone()
+    }
+
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        self.tools_changed.notify_one();
+    }
+}
+
+/// Re-list a server's tools after it reported `tools/list_changed`.
+///
+/// On success the slug is published on `tools_changed` so Porter can tell its
+/// own clients. Returns `Degraded` if the server could not be re-listed.
+pub(crate) async fn refresh_tools(
+    peer: &Peer<RoleClient>,
+    slug: &str,
+    config: &ServerConfig,
+    tools: &ArcSwap<Vec<Tool>>,
+    tools_changed: &broadcast::Sender<String>,
+) -> HealthState {
+    tracing::info!(server = %slug, "server reported tools list changed, re-listing");
+    let health = discover_tools(peer, slug, config, tools).await;
+    if health == HealthState::Healthy {
+        // No receivers just means no relay is running (e.g. `porter bench`)
+        let _ = tools_changed.send(slug.to_string());
+    }
+    health
+}
+
 /// A request to call a tool on a managed MCP server, with a one-shot channel for the response.
 pub(crate) struct ToolCallRequest {
     pub(crate) params: CallToolRequestParams,
@@ -37,8 +476,159 @@ pub(crate) struct ToolCallRequest {
 pub struct ServerHandle {
     pub(crate) slug: String,
     pub(crate) health_rx: watch::Receiver<HealthState>,
-    pub(crate) tools: Arc<RwLock<Vec<Tool>>>,
+    /// Swapped wholesale by the run loop so readers never wait on a refresh.
+    pub(crate) tools: Arc<ArcSwap<Vec<Tool>>>,
     pub(crate) call_tx: mpsc::Sender<ToolCallRequest>,
+    /// Token owned by this server's run loop; cancelling it stops just this server.
+    pub(crate) cancel: CancellationToken,
+    /// Tools are exposed under their original names (no `slug__` prefix).
+    pub(crate) flat_names: bool,
+    /// Negotiated protocol version and error rate, published by the run loop.
+    pub(crate) status: Arc<ServerStatus>,
+}
+
+/// Per-server state a run loop shares with the rest of Porter: what it
+/// publishes for its `ServerHandle`, the channels that trigger and announce
+/// tool re-listing, and the servers it waits for before its first connection.
+pub(crate) struct ServerShared {
+    /// Current tool list, swapped wholesale on each (re-)listing.
+    pub(crate) tools: Arc<ArcSwap<Vec<Tool>>>,
+    /// Negotiated protocol version, error rate and traffic counters.
+    pub(crate) status: Arc<ServerStatus>,
+    /// Registry-wide channel the slug is sent on after a re-listing.
+    pub(crate) tools_changed: broadcast::Sender<String>,
+    /// Notified by `PorterClient` when the server reports `tools/list_changed`.
+    pub(crate) list_changed: Arc<Notify>,
+    /// Servers that must be Healthy before the first connection (`depends_on`).
+    pub(crate) dependencies: Vec<Dependency>,
+}
+
+impl ServerShared {
+    /// Fresh state for a server that has not connected yet.
+    pub(crate) fn new(
+        tools_changed: broadcast::Sender<String>,
+        dependencies: Vec<Dependency>,
+    ) -> Self {
+        Self {
+            tools: Arc::new(ArcSwap::from_pointee(Vec::new())),
+            status: Arc::new(ServerStatus::default()),
+            tools_changed,
+            list_changed: Arc::new(Notify::new()),
+            dependencies,
+        }
+    }
+}
+
+/// Connection details a server's run loop publishes for its `ServerHandle`.
+#[derive(Debug, Default)]
+pub(crate) struct ServerStatus {
+    /// MCP protocol version negotiated on the most recent handshake.
+    pub(crate) protocol_version: std::sync::RwLock<Option<String>>,
+    /// Why that version holds the connection at Degraded (below
+    /// `min_protocol_version`); `None` when it is acceptable.
+    protocol_issue: std::sync::RwLock<Option<String>>,
+    /// The last `tools/list` attempt failed, so the server exposes a stale or
+    /// empty tool list until a later refresh succeeds.
+    listing_failed: AtomicBool,
+    /// Error rate over the health window, from `ErrorRateTracker::error_rate`.
+    pub(crate) error_rate: std::sync::RwLock<f64>,
+    /// Cumulative JSON size of forwarded call arguments.
+    request_bytes: AtomicU64,
+    /// Cumulative JSON size of successful call results.
+    response_bytes: AtomicU64,
+    /// Stdout lines that looked like JSON-RPC but failed to parse.
+    framing_errors: AtomicU64,
+}
+
+/// Point-in-time view of one managed server, as reported by `porter://health`,
+/// `/healthz` and `porter__info`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct ServerSnapshot {
+    /// Current health state.
+    pub health: HealthState,
+    /// MCP protocol version negotiated on the most recent handshake.
+    pub protocol_version: Option<String>,
+    /// Why the server is held at Degraded regardless of its error rate, if it is.
+    pub degraded_reason: Option<String>,
+}
+
+/// Cumulative payload sizes for one server's tool calls, in bytes of JSON,
+/// plus the count of malformed JSON-RPC frames it emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+pub struct TrafficStats {
+    /// Size of every call's `arguments` object.
+    pub request_bytes: u64,
+    /// Size of every successful `CallToolResult`.
+    pub response_bytes: u64,
+    /// Malformed JSON-RPC lines dropped from a STDIO server's stdout.
+    pub framing_errors: u64,
+}
+
+/// Length of `value` serialized as JSON, without buffering it.
+fn json_len<T: serde::Serialize>(value: &T) -> u64 {
+    struct Counter(u64);
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len() as u64;
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    let mut counter = Counter(0);
+    // Serializing plain MCP model types cannot fail; a partial count is still useful
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+impl ServerStatus {
+    /// Publish the tracker's current error rate and return the connected
+    /// server's health state.
+    ///
+    /// Too few samples to judge an error rate counts as Healthy: the server is
+    /// connected, and `Starting` would make it look not ready again after its
+    /// first call. While `degraded_reason` is set the result is at best Degraded.
+    pub(crate) fn record(&self, tracker: &ErrorRateTracker) -> HealthState {
+        *self.error_rate.write().unwrap_or_else(|e| e.into_inner()) = tracker.error_rate();
+        let measured = match tracker.health_state() {
+            HealthState::Starting => HealthState::Healthy,
+            measured => measured,
+        };
+        match measured {
+            HealthState::Healthy if self.degraded_reason().is_some() => HealthState::Degraded,
+            measured => measured,
+        }
+    }
+
+    /// Note the outcome of a tool listing (`discover_tools`/`refresh_tools`):
+    /// a Degraded listing holds the connection at Degraded until one succeeds.
+    pub(crate) fn record_listing(&self, listed: HealthState) {
+        self.listing_failed
+            .store(listed != HealthState::Healthy, Ordering::Relaxed);
+    }
+
+    /// Why the current connection is held at Degraded whatever its error rate.
+    pub(crate) fn degraded_reason(&self) -> Option<String> {
+        let mut reasons = Vec::new();
+        if self.listing_failed.load(Ordering::Relaxed) {
+            reasons.push("tool listing failed".to_string());
+        }
+        if let Some(issue) = self
+            .protocol_issue
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+        {
+            reasons.push(issue);
+        }
+        (!reasons.is_empty()).then(|| reasons.join("; "))
+    }
+
+    /// Count one malformed JSON-RPC frame.
+    pub(crate) fn record_framing_error(&self) {
+        self.framing_errors.fetch_add(1, Ordering::Relaxed);
+    }
 }
 
 impl ServerHandle {
@@ -47,9 +637,53 @@ impl ServerHandle {
         *self.health_rx.borrow()
     }
 
+    /// Returns the MCP protocol version negotiated on the most recent handshake,
+    /// or `None` before the first successful connection.
+    pub fn protocol_version(&self) -> Option<String> {
+        self.status
+            .protocol_version
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns why the server is held at Degraded regardless of its error
+    /// rate (e.g. a protocol version below `min_protocol_version`), if it is.
+    pub fn degraded_reason(&self) -> Option<String> {
+        self.status.degraded_reason()
+    }
+
+    /// Returns the server's health, protocol version and degraded reason together.
+    pub fn snapshot(&self) -> ServerSnapshot {
+        ServerSnapshot {
+            health: self.health(),
+            protocol_version: self.protocol_version(),
+            degraded_reason: self.degraded_reason(),
+        }
+    }
+
+    /// Returns the fraction of recent calls (and health probes) that failed.
+    pub fn error_rate(&self) -> f64 {
+        *self
+            .status
+            .error_rate
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns the cumulative request and response sizes of calls to this server
+    /// and its framing error count.
+    pub fn traffic(&self) -> TrafficStats {
+        TrafficStats {
+            request_bytes: self.status.request_bytes.load(Ordering::Relaxed),
+            response_bytes: self.status.response_bytes.load(Ordering::Relaxed),
+            framing_errors: self.status.framing_errors.load(Ordering::Relaxed),
+        }
+    }
+
     /// Returns a snapshot of the currently cached tools (namespaced).
-    pub async fn tools(&self) -> Vec<Tool> {
-        self.tools.read().await.clone()
+    pub fn tools(&self) -> Arc<Vec<Tool>> {
+        self.tools.load_full()
     }
 
     /// Invoke a tool on the managed server.
@@ -58,6 +692,11 @@ impl ServerHandle {
     /// the one-shot response. Returns an error if the server is unhealthy or
     /// the channel is closed.
     pub async fn call_tool(&self, params: CallToolRequestParams) -> crate::Result<CallToolResult> {
+        if let Some(arguments) = &params.arguments {
+            self.status
+                .request_bytes
+                .fetch_add(json_len(arguments), Ordering::Relaxed);
+        }
         let (response_tx, response_rx) = tokio::sync::oneshot::channel();
         let request = ToolCallRequest {
             params,
@@ -69,9 +708,13 @@ impl ServerHandle {
                 "server channel closed".to_string(),
             )
         })?;
-        response_rx.await.map_err(|_| {
+        let result = response_rx.await.map_err(|_| {
             crate::PorterError::Protocol(self.slug.clone(), "response channel dropped".to_string())
-        })?
+        })??;
+        self.status
+            .response_bytes
+            .fetch_add(json_len(&result), Ordering::Relaxed);
+        Ok(result)
     }
 }
 
@@ -79,19 +722,632 @@ impl ServerHandle {
 mod tests {
     use super::*;
     use std::sync::Arc;
-    use tokio::sync::{RwLock, mpsc, watch};
+    use tokio::sync::{mpsc, watch};
+
+    #[test]
+    fn test_prepare_tools_truncates_descriptions() {
+        let schema = Arc::new(serde_json::Map::new());
+        let tool = Tool {
+            name: "list_repos".into(),
+            title: None,
+            description: Some("List repositories".into()),
+            input_schema: schema,
+            output_schema: None,
+            annotations: None,
+            icons: None,
+            meta: None,
+        };
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        )
+        .unwrap();
+
+        let uncapped = prepare_tools("gh", &config, vec![tool.clone()]);
+        assert_eq!(
+            uncapped[0].description.as_deref(),
+            Some("[via gh] List repositories")
+        );
+
+        config.max_description_len = Some(12);
+        let capped = prepare_tools("gh", &config, vec![tool]);
+        assert_eq!(capped[0].name.as_ref(), "gh__list_repos");
+        assert_eq!(capped[0].description.as_deref(), Some("[via gh] Li…"));
+    }
+
+    #[test]
+    fn test_prepare_tools_applies_schema_overrides() {
+        let tool = |name: &str| Tool {
+            name: name.to_string().into(),
+            title: None,
+            description: None,
+            input_schema: Arc::new(
+                serde_json::json!({"type": "object", "required": ["a", "b"]})
+                    .as_object()
+                    .unwrap()
+                    .clone(),
+            ),
+            output_schema: None,
+            annotations: None,
+            icons: None,
+            meta: None,
+        };
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            schema_overrides.search = { required = ["a"] }
+            "#,
+        )
+        .unwrap();
+
+        let tools = prepare_tools("gh", &config, vec![tool("search"), tool("list_repos")]);
+        assert_eq!(tools[0].name.as_ref(), "gh__search");
+        assert_eq!(tools[0].input_schema["required"], serde_json::json!(["a"]));
+        assert_eq!(tools[0].input_schema["type"], "object");
+        // Override for one tool leaves the others untouched
+        assert_eq!(
+            tools[1].input_schema["required"],
+            serde_json::json!(["a", "b"])
+        );
+    }
+
+    /// Mock MCP server whose `tools/list` fails for the first `failures` calls
+    /// and whose `tools/call` always fails.
+    struct FlakyListServer {
+        failures: u32,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl rmcp::ServerHandler for FlakyListServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListToolsResult, rmcp::ErrorData> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if call < self.failures {
+                return Err(rmcp::ErrorData::internal_error("listing broken", None));
+            }
+            Ok(rmcp::model::ListToolsResult {
+                tools: vec![Tool {
+                    name: "search".into(),
+                    title: None,
+                    description: None,
+                    input_schema: Arc::new(serde_json::Map::new()),
+                    output_schema: None,
+                    annotations: None,
+                    icons: None,
+                    meta: None,
+                }],
+                next_cursor: None,
+                meta: None,
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<CallToolResult, rmcp::ErrorData> {
+            Err(rmcp::ErrorData::internal_error("probe broken", None))
+        }
+    }
+
+    /// Mock MCP server whose `tools/call` takes `delay` to answer.
+    struct SlowCallServer {
+        delay: Duration,
+    }
+
+    impl rmcp::ServerHandler for SlowCallServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<CallToolResult, rmcp::ErrorData> {
+            tokio::time::sleep(self.delay).await;
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                "ok",
+            )]))
+        }
+    }
+
+    /// Mock MCP server that answers every call with the `_meta` it received.
+    struct MetaEchoServer;
+
+    impl rmcp::ServerHandler for MetaEchoServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<CallToolResult, rmcp::ErrorData> {
+            let meta = serde_json::to_string(&context.meta).unwrap_or_default();
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                meta,
+            )]))
+        }
+    }
+
+    /// Mock MCP server whose tool list grows by one each time `add_tool` is called,
+    /// announcing the change with `notifications/tools/list_changed`.
+    #[derive(Default)]
+    struct GrowingToolsServer {
+        count: std::sync::atomic::AtomicUsize,
+    }
+
+    impl rmcp::ServerHandler for GrowingToolsServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .enable_tool_list_changed()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn list_tools(
+            &self,
+            _request: Option<rmcp::model::PaginatedRequestParams>,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::ListToolsResult, rmcp::ErrorData> {
+            let count = self.count.load(std::sync::atomic::Ordering::SeqCst);
+            let tools = std::iter::once("add_tool".to_string())
+                .chain((0..count).map(|i| format!("extra_{}", i)))
+                .map(|name| Tool::new(name, "", Arc::new(serde_json::Map::new())))
+                .collect();
+            Ok(rmcp::model::ListToolsResult {
+                tools,
+                next_cursor: None,
+                meta: None,
+            })
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<CallToolResult, rmcp::ErrorData> {
+            self.count.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            let _ = context.peer.notify_tool_list_changed().await;
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                "ok",
+            )]))
+        }
+    }
+
+    /// Mock MCP server whose first `failures` tool calls fail with `error`.
+    struct FlakyCallServer {
+        failures: u32,
+        error: rmcp::ErrorData,
+        calls: std::sync::atomic::AtomicU32,
+    }
+
+    impl FlakyCallServer {
+        fn new(failures: u32, error: rmcp::ErrorData) -> Arc<Self> {
+            Arc::new(Self {
+                failures,
+                error,
+                calls: std::sync::atomic::AtomicU32::new(0),
+            })
+        }
+    }
+
+    impl rmcp::ServerHandler for FlakyCallServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn call_tool(
+            &self,
+            _request: CallToolRequestParams,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<CallToolResult, rmcp::ErrorData> {
+            let call = self.calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            if call < self.failures {
+                return Err(self.error.clone());
+            }
+            Ok(CallToolResult::success(vec![rmcp::model::Content::text(
+                "ok",
+            )]))
+        }
+    }
+
+    /// Connect a client to an in-process `FlakyListServer`.
+    async fn connect_flaky(failures: u32) -> rmcp::service::RunningService<RoleClient, ClientInfo> {
+        connect_mock(FlakyListServer {
+            failures,
+            calls: std::sync::atomic::AtomicU32::new(0),
+        })
+        .await
+    }
+
+    /// Connect a client to an in-process mock server.
+    async fn connect_mock(
+        server: impl rmcp::ServerHandler,
+    ) -> rmcp::service::RunningService<RoleClient, ClientInfo> {
+        connect_mock_as(server, ClientInfo::default()).await
+    }
+
+    /// Connect `client` to an in-process mock server.
+    async fn connect_mock_as<C: ClientHandler>(
+        server: impl rmcp::ServerHandler,
+        client: C,
+    ) -> rmcp::service::RunningService<RoleClient, C> {
+        use rmcp::ServiceExt;
+
+        let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(async move {
+            if let Ok(running) = server.serve(server_io).await {
+                let _ = running.waiting().await;
+            }
+        });
+        client.serve(client_io).await.unwrap()
+    }
+
+    /// Connect a client to an in-process `FlakyListServer` and run discovery.
+    async fn discover_against(failures: u32) -> (HealthState, Vec<Tool>) {
+        let client = connect_flaky(failures).await;
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        let tools = ArcSwap::from_pointee(Vec::new());
+        let health = discover_tools(client.peer(), "mock", &config, &tools).await;
+        let tools = tools.load().to_vec();
+        (health, tools)
+    }
+
+    #[tokio::test]
+    async fn test_discover_tools_retries_then_succeeds() {
+        let (health, tools) = discover_against(LIST_TOOLS_ATTEMPTS - 1).await;
+        assert_eq!(health, HealthState::Healthy);
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].name.as_ref(), "mock__search");
+    }
+
+    #[tokio::test]
+    async fn test_discover_tools_persistent_failure_is_degraded() {
+        let (health, tools) = discover_against(u32::MAX).await;
+        assert_eq!(health, HealthState::Degraded);
+        assert!(tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_failing_health_probe_degrades_server() {
+        let client = connect_flaky(0).await;
+        // A healthy window of client calls, then idle-time probes start failing
+        let mut tracker = ErrorRateTracker::new(Duration::from_secs(60));
+        for _ in 0..9 {
+            tracker.record_success();
+        }
+        assert_eq!(tracker.health_state(), HealthState::Healthy);
+
+        let health = run_health_probe(
+            client.peer(),
+            "mock",
+            "ping",
+            Duration::from_secs(5),
+            &mut tracker,
+        )
+        .await;
+        assert_eq!(health, HealthState::Degraded);
+    }
+
+    #[tokio::test]
+    async fn test_protocol_version_captured_and_checked() {
+        let client = connect_flaky(0).await;
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        let status = ServerStatus::default();
+        let mut tracker = ErrorRateTracker::new(Duration::from_secs(60));
+        for _ in 0..5 {
+            tracker.record_success();
+        }
+
+        check_protocol_version(client.peer(), "mock", &config, &status);
+        assert_eq!(status.record(&tracker), HealthState::Healthy);
+        let negotiated = status
+            .protocol_version
+            .read()
+            .unwrap()
+            .clone()
+            .expect("version captured");
+        assert_eq!(
+            negotiated,
+            rmcp::model::ProtocolVersion::default().to_string()
+        );
+
+        config.min_protocol_version = Some("2024-11-05".to_string());
+        check_protocol_version(client.peer(), "mock", &config, &status);
+        assert_eq!(status.record(&tracker), HealthState::Healthy);
+        assert_eq!(status.degraded_reason(), None);
+
+        // A clean error rate does not lift the protocol version floor
+        config.min_protocol_version = Some("2999-01-01".to_string());
+        check_protocol_version(client.peer(), "mock", &config, &status);
+        assert_eq!(status.record(&tracker), HealthState::Degraded);
+        let reason = status.degraded_reason().expect("reason kept");
+        assert!(
+            reason.contains("min_protocol_version 2999-01-01"),
+            "{}",
+            reason
+        );
+
+        // ...but a bad one still makes it Unhealthy
+        for _ in 0..10 {
+            tracker.record_error();
+        }
+        assert_eq!(status.record(&tracker), HealthState::Unhealthy);
+    }
+
+    fn call_params(timeout_ms: Option<u64>) -> CallToolRequestParams {
+        CallToolRequestParams {
+            name: "slow".into(),
+            arguments: None,
+            task: None,
+            meta: timeout_ms.map(|ms| {
+                let mut meta = rmcp::model::Meta::new();
+                meta.0
+                    .insert(CLIENT_TIMEOUT_META_KEY.to_string(), serde_json::json!(ms));
+                meta
+            }),
+        }
+    }
+
+    #[test]
+    fn test_call_timeout_takes_shorter_bound() {
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(call_timeout(&config, &call_params(None)), None);
+        assert_eq!(
+            call_timeout(&config, &call_params(Some(500))),
+            Some(Duration::from_millis(500))
+        );
+        config.call_timeout_secs = Some(1);
+        assert_eq!(
+            call_timeout(&config, &call_params(None)),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(
+            call_timeout(&config, &call_params(Some(5_000))),
+            Some(Duration::from_secs(1))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_deadline_cuts_call_short() {
+        let client = connect_mock(SlowCallServer {
+            delay: Duration::from_secs(10),
+        })
+        .await;
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        config.call_timeout_secs = Some(30);
+
+        let started = tokio::time::Instant::now();
+        let result = forward_call(client.peer(), "mock", &config, call_params(Some(100))).await;
+        assert!(
+            matches!(result, Err(PorterError::Protocol(..))),
+            "{:?}",
+            result
+        );
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn test_porter_meta_keys_are_not_forwarded() {
+        let client = connect_mock(MetaEchoServer).await;
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        let mut params = call_params(Some(5_000));
+        let meta = params.meta.get_or_insert_with(rmcp::model::Meta::new);
+        meta.0
+            .insert("porter/ifNoneMatch".to_string(), serde_json::json!("abc"));
+        meta.0
+            .insert("traceparent".to_string(), serde_json::json!("00-abc-01"));
+
+        let result = forward_call(client.peer(), "mock", &config, params)
+            .await
+            .unwrap();
+        let text = result.content[0].as_text().unwrap().text.clone();
+        let seen: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert_eq!(seen["traceparent"], "00-abc-01");
+        assert!(
+            seen.as_object()
+                .unwrap()
+                .keys()
+                .all(|key| !key.starts_with(PORTER_META_PREFIX)),
+            "{}",
+            seen
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tools_list_changed_triggers_relist() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        let notify = Arc::new(Notify::new());
+        let client = connect_mock_as(
+            GrowingToolsServer::default(),
+            PorterClient::new(&config, notify.clone()),
+        )
+        .await;
+        let tools = ArcSwap::from_pointee(Vec::new());
+        let (changed_tx, mut changed_rx) = broadcast::channel(4);
+        discover_tools(client.peer(), "mock", &config, &tools).await;
+        assert_eq!(tools.load().len(), 1);
+
+        client
+            .peer()
+            .call_tool(CallToolRequestParams {
+                name: "add_tool".into(),
+                arguments: None,
+                task: None,
+                meta: None,
+            })
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), notify.notified())
+            .await
+            .expect("tools/list_changed should reach the client handler");
+
+        let health = refresh_tools(client.peer(), "mock", &config, &tools, &changed_tx).await;
+        assert_eq!(health, HealthState::Healthy);
+        let names: Vec<String> = tools.load().iter().map(|t| t.name.to_string()).collect();
+        assert_eq!(names, vec!["mock__add_tool", "mock__extra_0"]);
+        assert_eq!(changed_rx.try_recv().unwrap(), "mock");
+    }
+
+    /// Forward one call to a `FlakyCallServer` with `call_retries` set.
+    async fn call_flaky(
+        server: Arc<FlakyCallServer>,
+        retries: u32,
+    ) -> crate::Result<CallToolResult> {
+        let client = connect_mock(server).await;
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        config.call_retries = retries;
+        forward_call(client.peer(), "mock", &config, call_params(None)).await
+    }
+
+    #[tokio::test]
+    async fn test_call_retries_mask_transient_failure() {
+        let glitch = || rmcp::ErrorData::internal_error("glitch", None);
+
+        let server = FlakyCallServer::new(1, glitch());
+        assert!(call_flaky(server.clone(), 1).await.is_ok());
+        assert_eq!(server.calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        let server = FlakyCallServer::new(1, glitch());
+        assert!(call_flaky(server.clone(), 0).await.is_err());
+        assert_eq!(server.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_call_retries_skip_non_transient_errors() {
+        let server = FlakyCallServer::new(1, rmcp::ErrorData::invalid_params("bad args", None));
+        let result = call_flaky(server.clone(), 3).await;
+        assert!(
+            matches!(result, Err(PorterError::Protocol(..))),
+            "{:?}",
+            result
+        );
+        assert_eq!(server.calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_client_info_overrides() {
+        let mut config: ServerConfig = toml::from_str(
+            r#"
+            slug = "gh"
+            transport = "stdio"
+            command = "gh-mcp"
+            "#,
+        )
+        .unwrap();
+        let default_info = client_info(&config);
+        assert_eq!(default_info, ClientInfo::default());
+
+        config.client_name = Some("acme-agent".to_string());
+        config.client_version = Some("2.1.0".to_string());
+        config.client_roots = true;
+        let info = client_info(&config);
+        assert_eq!(info.client_info.name, "acme-agent");
+        assert_eq!(info.client_info.version, "2.1.0");
+        assert!(info.capabilities.roots.is_some());
+    }
 
     #[tokio::test]
     async fn test_server_handle_health() {
         let (health_tx, health_rx) = watch::channel(HealthState::Starting);
         let (call_tx, _call_rx) = mpsc::channel(32);
-        let tools = Arc::new(RwLock::new(Vec::<Tool>::new()));
+        let tools = Arc::new(ArcSwap::from_pointee(Vec::<Tool>::new()));
 
         let handle = ServerHandle {
             slug: "test".to_string(),
             health_rx,
             tools,
             call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
         };
 
         assert_eq!(handle.health(), HealthState::Starting);
@@ -105,30 +1361,158 @@ mod tests {
     async fn test_server_handle_tools_empty() {
         let (_health_tx, health_rx) = watch::channel(HealthState::Starting);
         let (call_tx, _call_rx) = mpsc::channel(32);
-        let tools = Arc::new(RwLock::new(Vec::<Tool>::new()));
+        let tools = Arc::new(ArcSwap::from_pointee(Vec::<Tool>::new()));
 
         let handle = ServerHandle {
             slug: "test".to_string(),
             health_rx,
             tools,
             call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
         };
 
-        let tool_list = handle.tools().await;
+        let tool_list = handle.tools();
         assert!(tool_list.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_call_tool_counts_payload_bytes() {
+        let (_health_tx, health_rx) = watch::channel(HealthState::Healthy);
+        let (call_tx, mut call_rx) = mpsc::channel::<ToolCallRequest>(1);
+        let handle = ServerHandle {
+            slug: "test".to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(Vec::new())),
+            call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        let output = "x".repeat(100);
+        let reply = CallToolResult::success(vec![rmcp::model::Content::text(output)]);
+        let expected_response = serde_json::to_vec(&reply).unwrap().len() as u64;
+        tokio::spawn(async move {
+            while let Some(req) = call_rx.recv().await {
+                let _ = req.response_tx.send(Ok(reply.clone()));
+            }
+        });
+
+        let arguments = serde_json::json!({"query": "abc"}).as_object().cloned();
+        for _ in 0..2 {
+            handle
+                .call_tool(CallToolRequestParams {
+                    name: "search".into(),
+                    arguments: arguments.clone(),
+                    task: None,
+                    meta: None,
+                })
+                .await
+                .unwrap();
+        }
+        assert_eq!(
+            handle.traffic(),
+            TrafficStats {
+                request_bytes: 2 * r#"{"query":"abc"}"#.len() as u64,
+                response_bytes: 2 * expected_response,
+                framing_errors: 0,
+            }
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_tool_reads_consistent_during_refresh() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            slug = "mock"
+            transport = "stdio"
+            command = "mock"
+            "#,
+        )
+        .unwrap();
+        let client = connect_mock(GrowingToolsServer::default()).await;
+        let tools = Arc::new(ArcSwap::from_pointee(Vec::new()));
+        discover_tools(client.peer(), "mock", &config, &tools).await;
+
+        let (_health_tx, health_rx) = watch::channel(HealthState::Healthy);
+        let handle = ServerHandle {
+            slug: "mock".to_string(),
+            health_rx,
+            tools: tools.clone(),
+            call_tx: mpsc::channel(1).0,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        let registry = Arc::new(crate::PorterRegistry::from_handles(
+            std::collections::HashMap::from([("mock".to_string(), handle)]),
+        ));
+        let done = CancellationToken::new();
+
+        // Each reader sees either the list before a refresh or the one after:
+        // never empty, never missing a tool, never shrinking
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let (registry, done) = (registry.clone(), done.clone());
+                tokio::spawn(async move {
+                    let mut seen = 0;
+                    while !done.is_cancelled() {
+                        let names: Vec<String> = registry
+                            .tools()
+                            .await
+                            .iter()
+                            .map(|t| t.name.to_string())
+                            .collect();
+                        assert!(!names.is_empty(), "empty tool list");
+                        let expected: Vec<String> = std::iter::once("mock__add_tool".to_string())
+                            .chain((0..names.len() - 1).map(|i| format!("mock__extra_{}", i)))
+                            .collect();
+                        assert_eq!(names, expected, "partial tool list");
+                        assert!(names.len() >= seen, "tool list went backwards");
+                        seen = names.len();
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+
+        let (changed_tx, _) = broadcast::channel(64);
+        for _ in 0..50 {
+            client
+                .peer()
+                .call_tool(CallToolRequestParams {
+                    name: "add_tool".into(),
+                    arguments: None,
+                    task: None,
+                    meta: None,
+                })
+                .await
+                .unwrap();
+            let health = refresh_tools(client.peer(), "mock", &config, &tools, &changed_tx).await;
+            assert_eq!(health, HealthState::Healthy);
+        }
+        done.cancel();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+        assert_eq!(registry.tools().await.len(), 51);
+    }
+
     #[tokio::test]
     async fn test_server_handle_call_tool_unhealthy_when_channel_closed() {
         let (_health_tx, health_rx) = watch::channel(HealthState::Unhealthy);
         let (call_tx, call_rx) = mpsc::channel(1);
-        let tools = Arc::new(RwLock::new(Vec::<Tool>::new()));
+        let tools = Arc::new(ArcSwap::from_pointee(Vec::<Tool>::new()));
 
         let handle = ServerHandle {
             slug: "test-server".to_string(),
             health_rx,
             tools,
             call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
         };
 
         // Drop receiver to simulate a closed channel
@@ -0,0 +1,407 @@
+//! Legacy SSE client transport for Porter.
+//!
+//! Older MCP servers speak the HTTP+SSE transport (protocol 2024-11-05) rather
+//! than Streamable HTTP. The client opens a long-lived `GET` event stream; the
+//! server's first `endpoint` event names the URL that client messages are
+//! `POST`ed to, and every later `message` event carries one JSON-RPC message.
+//!
+//! rmcp no longer ships a client for this transport, so `SseClientTransport`
+//! implements rmcp's `Transport` trait directly on top of reqwest. An
+//! `endpoint` on a different origin than the event stream is refused, so a
+//! server cannot redirect client messages to another host. Lifecycle
+//! (reconnect, backoff, health) is handled by the shared HTTP run loop.
+
+use futures::StreamExt;
+use rmcp::RoleClient;
+use rmcp::service::{RxJsonRpcMessage, TxJsonRpcMessage};
+use rmcp::transport::Transport;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::PorterError;
+
+/// A single parsed server-sent event.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SseEvent {
+    event: String,
+    data: String,
+}
+
+/// Incremental parser for a `text/event-stream` body.
+///
+/// Bytes are buffered until a blank line terminates an event, so chunks may
+/// split events (or UTF-8 sequences) at arbitrary boundaries.
+#[derive(Default)]
+struct SseEventParser {
+    buf: Vec<u8>,
+}
+
+impl SseEventParser {
+    /// Feed a chunk of the stream and return every event it completed.
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buf.extend(chunk.iter().filter(|b| **b != b'\r'));
+        let mut events = Vec::new();
+        while let Some(pos) = self.buf.windows(2).position(|w| w == b"\n\n") {
+            let raw: Vec<u8> = self.buf.drain(..pos + 2).collect();
+            if let Some(event) = parse_event(&String::from_utf8_lossy(&raw[..pos])) {
+                events.push(event);
+            }
+        }
+        events
+    }
+}
+
+/// Parse one event block. Returns `None` for comment-only or data-less blocks.
+fn parse_event(block: &str) -> Option<SseEvent> {
+    let mut event = "message".to_string();
+    let mut data: Vec<&str> = Vec::new();
+    for line in block.lines() {
+        if line.starts_with(':') {
+            continue;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => event = value.to_string(),
+            "data" => data.push(value),
+            _ => {}
+        }
+    }
+    if data.is_empty() {
+        return None;
+    }
+    Some(SseEvent {
+        event,
+        data: data.join("\n"),
+    })
+}
+
+/// Client side of the legacy HTTP+SSE MCP transport.
+pub(crate) struct SseClientTransport {
+    slug: String,
+    client: reqwest::Client,
+    /// URL announced by the server's `endpoint` event; client messages are POSTed here.
+    endpoint: reqwest::Url,
+    rx: mpsc::Receiver<RxJsonRpcMessage<RoleClient>>,
+    /// Stops the background event-stream reader when the transport closes.
+    reader_cancel: CancellationToken,
+}
+
+impl SseClientTransport {
+    /// Open the event stream at `url` and wait for the server's `endpoint` event.
+    ///
+    /// Fails if the endpoint's scheme, host or port differs from `url`'s.
+    pub(crate) async fn connect(url: &str, slug: &str) -> crate::Result<Self> {
+        let transport_err = |e: String| PorterError::Transport(slug.to_string(), e);
+
+        let base = reqwest::Url::parse(url).map_err(|e| transport_err(e.to_string()))?;
+        let client = reqwest::Client::new();
+        let response = client
+            .get(base.clone())
+            .header(reqwest::header::ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| transport_err(e.to_string()))?;
+
+        let mut stream = response.bytes_stream();
+        let mut parser = SseEventParser::default();
+        let mut pending = Vec::new();
+
+        // The first `endpoint` event tells us where to POST; anything after it
+        // in the same chunk is already a message and must not be lost.
+        let endpoint = 'outer: loop {
+            let chunk = match stream.next().await {
+                Some(Ok(chunk)) => chunk,
+                Some(Err(e)) => return Err(transport_err(e.to_string())),
+                None => {
+                    return Err(transport_err(
+                        "SSE stream ended before endpoint event".to_string(),
+                    ));
+                }
+            };
+            let mut events = parser.push(&chunk).into_iter();
+            while let Some(event) = events.next() {
+                if event.event == "endpoint" {
+                    let endpoint = base
+                        .join(event.data.trim())
+                        .map_err(|e| transport_err(e.to_string()))?;
+                    if endpoint.origin() != base.origin() {
+                        return Err(transport_err(format!(
+                            "SSE endpoint {} is not on the same origin as {}",
+                            endpoint, base
+                        )));
+                    }
+                    pending.extend(events);
+                    break 'outer endpoint;
+                }
+            }
+        };
+
+        let (tx, rx) = mpsc::channel(64);
+        let reader_cancel = CancellationToken::new();
+        let cancel = reader_cancel.clone();
+        let reader_slug = slug.to_string();
+
+        tokio::spawn(async move {
+            let forward = |event: SseEvent| {
+                if event.event != "message" {
+                    return None;
+                }
+                match serde_json::from_str::<RxJsonRpcMessage<RoleClient>>(&event.data) {
+                    Ok(msg) => Some(msg),
+                    Err(e) => {
+                        tracing::warn!(server = %reader_slug, error = %e, "discarding malformed SSE message");
+                        None
+                    }
+                }
+            };
+            for msg in pending.into_iter().filter_map(forward) {
+                if tx.send(msg).await.is_err() {
+                    return;
+                }
+            }
+            loop {
+                tokio::select! {
+                    chunk = stream.next() => {
+                        let Some(Ok(chunk)) = chunk else { break };
+                        for msg in parser.push(&chunk).into_iter().filter_map(forward) {
+                            if tx.send(msg).await.is_err() {
+                                return;
+                            }
+                        }
+                    }
+                    _ = cancel.cancelled() => break,
+                }
+            }
+            // tx dropped here — rmcp sees the transport as closed
+        });
+
+        Ok(Self {
+            slug: slug.to_string(),
+            client,
+            endpoint,
+            rx,
+            reader_cancel,
+        })
+    }
+}
+
+impl Transport<RoleClient> for SseClientTransport {
+    type Error = PorterError;
+
+    fn send(
+        &mut self,
+        item: TxJsonRpcMessage<RoleClient>,
+    ) -> impl Future<Output = Result<(), Self::Error>> + Send + 'static {
+        let client = self.client.clone();
+        let endpoint = self.endpoint.clone();
+        let slug = self.slug.clone();
+        async move {
+            client
+                .post(endpoint)
+                .json(&item)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status())
+                .map_err(|e| PorterError::Transport(slug, e.to_string()))?;
+            Ok(())
+        }
+    }
+
+    async fn receive(&mut self) -> Option<RxJsonRpcMessage<RoleClient>> {
+        self.rx.recv().await
+    }
+
+    async fn close(&mut self) -> Result<(), Self::Error> {
+        self.reader_cancel.cancel();
+        Ok(())
+    }
+}
+
+impl Drop for SseClientTransport {
+    fn drop(&mut self) {
+        self.reader_cancel.cancel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::Router;
+    use axum::response::sse::{Event, Sse};
+    use axum::routing::get;
+    use std::convert::Infallible;
+
+    #[test]
+    fn test_parser_single_event() {
+        let mut parser = SseEventParser::default();
+        let events = parser.push(b"event: endpoint\ndata: /messages?sessionId=1\n\n");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: "endpoint".to_string(),
+                data: "/messages?sessionId=1".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parser_split_chunks_and_crlf() {
+        let mut parser = SseEventParser::default();
+        assert!(parser.push(b"data: {\"a\"").is_empty());
+        let events = parser.push(b":1}\r\n\r\n: keep-alive\r\n\r\n");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event, "message");
+        assert_eq!(events[0].data, "{\"a\":1}");
+    }
+
+    #[test]
+    fn test_parser_multiline_data() {
+        let mut parser = SseEventParser::default();
+        let events = parser.push(b"data: one\ndata: two\n\n");
+        assert_eq!(events[0].data, "one\ntwo");
+    }
+
+    #[tokio::test]
+    async fn test_connect_resolves_relative_endpoint() {
+        let url = serve_endpoint("/messages?sessionId=abc").await;
+        let transport = SseClientTransport::connect(&url, "legacy").await.unwrap();
+        assert_eq!(
+            transport.endpoint.as_str(),
+            url.replace("/sse", "/messages?sessionId=abc")
+        );
+    }
+
+    /// Serve an SSE stream at `/sse` whose only event is `endpoint` with `data`.
+    async fn serve_endpoint(data: &'static str) -> String {
+        let app = Router::new().route(
+            "/sse",
+            get(move || async move {
+                Sse::new(futures::stream::iter(vec![Ok::<_, Infallible>(
+                    Event::default().event("endpoint").data(data),
+                )]))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/sse", addr)
+    }
+
+    #[tokio::test]
+    async fn test_connect_rejects_cross_origin_endpoint() {
+        for endpoint in [
+            "http://evil.example.com/messages",
+            "https://127.0.0.1/messages",
+            "//evil.example.com/messages",
+        ] {
+            let url = serve_endpoint(endpoint).await;
+            let result = SseClientTransport::connect(&url, "legacy").await;
+            assert!(
+                matches!(&result, Err(PorterError::Transport(_, msg)) if msg.contains("same origin")),
+                "{}",
+                endpoint
+            );
+        }
+    }
+
+    /// Minimal legacy SSE MCP server: `GET /sse` streams the endpoint event and
+    /// then every response; `POST /messages` answers `initialize` and `tools/list`.
+    async fn serve_legacy_mcp() -> String {
+        use axum::extract::State;
+        use axum::http::StatusCode;
+        use axum::routing::post;
+        use std::sync::{Arc, Mutex};
+
+        type Responses = (
+            mpsc::Sender<String>,
+            Arc<Mutex<Option<mpsc::Receiver<String>>>>,
+        );
+
+        async fn stream(
+            State((_, rx)): State<Responses>,
+        ) -> Sse<impl futures::Stream<Item = Result<Event, Infallible>>> {
+            let rx = rx.lock().unwrap().take().expect("one SSE client");
+            let endpoint = Event::default().event("endpoint").data("/messages");
+            let messages = futures::stream::unfold(rx, |mut rx| async move {
+                let data = rx.recv().await?;
+                Some((Event::default().event("message").data(data), rx))
+            });
+            Sse::new(
+                futures::stream::once(async { endpoint })
+                    .chain(messages)
+                    .map(Ok),
+            )
+        }
+
+        async fn message(
+            State((tx, _)): State<Responses>,
+            axum::Json(request): axum::Json<serde_json::Value>,
+        ) -> StatusCode {
+            let result = match request["method"].as_str() {
+                Some("initialize") => serde_json::json!({
+                    "protocolVersion": "2024-11-05",
+                    "capabilities": {"tools": {}},
+                    "serverInfo": {"name": "legacy", "version": "1.0.0"},
+                }),
+                Some("tools/list") => serde_json::json!({
+                    "tools": [{"name": "echo", "inputSchema": {"type": "object"}}],
+                }),
+                // Notifications get no response
+                _ => return StatusCode::ACCEPTED,
+            };
+            let response = serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "result": result,
+            });
+            let _ = tx.send(response.to_string()).await;
+            StatusCode::ACCEPTED
+        }
+
+        let (tx, rx) = mpsc::channel(16);
+        let app = Router::new()
+            .route("/sse", get(stream))
+            .route("/messages", post(message))
+            .with_state((tx, Arc::new(Mutex::new(Some(rx)))));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+        format!("http://{}/sse", addr)
+    }
+
+    #[tokio::test]
+    async fn test_initialize_and_list_tools_round_trip() {
+        use rmcp::ServiceExt;
+
+        let url = serve_legacy_mcp().await;
+        let transport = SseClientTransport::connect(&url, "legacy").await.unwrap();
+        let client = rmcp::model::ClientInfo::default()
+            .serve(transport)
+            .await
+            .unwrap();
+        assert_eq!(
+            client
+                .peer()
+                .peer_info()
+                .unwrap()
+                .protocol_version
+                .to_string(),
+            "2024-11-05"
+        );
+
+        let tools = client.peer().list_all_tools().await.unwrap();
+        let names: Vec<&str> = tools.iter().map(|t| t.name.as_ref()).collect();
+        assert_eq!(names, vec!["echo"]);
+        let _ = client.cancel().await;
+    }
+
+    #[tokio::test]
+    async fn test_connect_invalid_url() {
+        let result = SseClientTransport::connect("not a url", "legacy").await;
+        assert!(matches!(result, Err(PorterError::Transport(slug, _)) if slug == "legacy"));
+    }
+}
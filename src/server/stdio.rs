@@ -6,26 +6,32 @@
 //! - Uses raw `tokio::process::Command` instead of `TokioChildProcess` to enable
 //!   noisy-server stdout filtering (non-JSON lines discarded silently).
 //! - Stdout is piped through a BufReader task that filters non-JSON lines before
-//!   passing valid JSON to the rmcp transport.
-//! - A restart loop with exponential backoff (1s → 30s cap) handles crashed servers.
+//!   passing valid JSON to the rmcp transport. Lines that look like JSON-RPC
+//!   but fail to parse (e.g. truncated frames) are logged at warn and counted
+//!   as framing errors instead.
+//! - A restart loop with exponential backoff (1s → 30s cap) handles crashed servers;
+//!   with `immediate_first_retry` the first failure is retried without waiting.
 //! - Health state transitions: Starting → Healthy → Degraded → Unhealthy.
 
 use std::sync::Arc;
 use std::time::Duration;
 
-use rmcp::model::Tool;
 use rmcp::service::RunningService;
 use rmcp::{RoleClient, ServiceExt};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::{Child, Command};
-use tokio::sync::{Mutex, RwLock, mpsc, watch};
+use tokio::sync::{Mutex, Notify, broadcast, mpsc, watch};
 use tokio_util::sync::CancellationToken;
 
-use crate::config::{ServerConfig, resolve_env_vars};
+use crate::config::{ServerConfig, expand_path, resolve_env_vars};
 use crate::error::PorterError;
-use crate::namespace::namespace_tool;
+use crate::server::budget::SpawnBudget;
 use crate::server::health::{ErrorRateTracker, HealthState, StderrBuffer};
-use crate::server::{BACKOFF_INITIAL, BACKOFF_MAX, MAX_FAILURES, ServerHandle, ToolCallRequest};
+use crate::server::{
+    BACKOFF_INITIAL, Dependency, MAX_FAILURES, PorterClient, ServerHandle, ServerShared,
+    ServerStatus, ToolCallRequest, check_protocol_version, discover_tools, forward_call,
+    health_probe_ticker, next_retry_delay, refresh_tools, run_health_probe, wait_for_dependencies,
+};
 
 /// Spawn the child process for a STDIO MCP server.
 ///
@@ -49,7 +55,9 @@ fn spawn_stdio_child(config: &ServerConfig) -> crate::Result<Child> {
     }
 
     if let Some(ref cwd) = config.cwd {
-        cmd.current_dir(cwd);
+        // Configs loaded from a file are already resolved; this covers `~` in
+        // configs built programmatically.
+        cmd.current_dir(expand_path(cwd, None));
     }
 
     cmd.stdin(std::process::Stdio::piped());
@@ -60,15 +68,39 @@ fn spawn_stdio_child(config: &ServerConfig) -> crate::Result<Child> {
         .map_err(|e| PorterError::Transport(config.slug.clone(), e.to_string()))
 }
 
+/// How the stdout filter treats one line from the child process.
+#[derive(Debug, PartialEq, Eq)]
+enum StdoutLine {
+    /// Valid JSON, forwarded to the rmcp transport.
+    Json,
+    /// Log output or other noise that is plainly not JSON.
+    Noise,
+    /// Starts like a JSON-RPC object but does not parse: a protocol-breaking frame.
+    Malformed,
+}
+
+/// Classify one stdout line; only lines that open a JSON-RPC object count as malformed.
+fn classify_stdout_line(line: &str) -> StdoutLine {
+    if serde_json::from_str::<serde_json::Value>(line).is_ok() {
+        StdoutLine::Json
+    } else if line.trim_start().starts_with('{') && line.contains("\"jsonrpc\"") {
+        StdoutLine::Malformed
+    } else {
+        StdoutLine::Noise
+    }
+}
+
 /// Start a background task that filters stdout from the child process.
 ///
-/// Non-JSON lines are discarded with a debug log. Valid JSON lines are
+/// Non-JSON lines are discarded with a debug log; malformed JSON-RPC lines are
+/// discarded with a warning and counted in `status`. Valid JSON lines are
 /// forwarded to the returned `DuplexStream` which rmcp reads as its transport.
 ///
 /// The duplex stream carries raw JSON-RPC newline-delimited messages.
 fn start_stdout_filter(
-    child_stdout: tokio::process::ChildStdout,
+    child_stdout: impl tokio::io::AsyncRead + Unpin + Send + 'static,
     slug: String,
+    status: Arc<ServerStatus>,
     cancel: CancellationToken,
 ) -> tokio::io::ReadHalf<tokio::io::DuplexStream> {
     let (client_side, server_side) = tokio::io::duplex(65536);
@@ -84,21 +116,30 @@ fn start_stdout_filter(
             tokio::select! {
                 line_result = lines.next_line() => {
                     match line_result {
-                        Ok(Some(line)) => {
+                        Ok(Some(line)) => match classify_stdout_line(&line) {
                             // Only forward valid JSON lines to rmcp transport
-                            if serde_json::from_str::<serde_json::Value>(&line).is_ok() {
+                            StdoutLine::Json => {
                                 let with_newline = format!("{}\n", line);
                                 if writer.write_all(with_newline.as_bytes()).await.is_err() {
                                     break;
                                 }
-                            } else {
+                            }
+                            StdoutLine::Noise => {
                                 tracing::debug!(
                                     server = %slug,
                                     line = %line,
                                     "discarding non-JSON stdout line"
                                 );
                             }
-                        }
+                            StdoutLine::Malformed => {
+                                status.record_framing_error();
+                                tracing::warn!(
+                                    server = %slug,
+                                    line = %line,
+                                    "discarding malformed JSON-RPC frame on stdout"
+                                );
+                            }
+                        },
                         Ok(None) | Err(_) => {
                             // EOF or read error — drop writer to signal EOF to reader
                             break;
@@ -156,8 +197,10 @@ async fn spawn_and_handshake(
     config: &ServerConfig,
     slug: &str,
     stderr_buf: Arc<Mutex<StderrBuffer>>,
+    list_changed: Arc<Notify>,
+    status: Arc<ServerStatus>,
     cancel: CancellationToken,
-) -> crate::Result<(RunningService<RoleClient, ()>, Child)> {
+) -> crate::Result<(RunningService<RoleClient, PorterClient>, Child)> {
     let mut child = spawn_stdio_child(config)?;
 
     let child_stdin = child.stdin.take().ok_or_else(|| {
@@ -171,7 +214,8 @@ async fn spawn_and_handshake(
     })?;
 
     // Start background IO tasks
-    let filtered_reader = start_stdout_filter(child_stdout, slug.to_string(), cancel.clone());
+    let filtered_reader =
+        start_stdout_filter(child_stdout, slug.to_string(), status, cancel.clone());
     start_stderr_drain(child_stderr, slug.to_string(), stderr_buf, cancel.clone());
 
     // The transport is (reader, writer): rmcp reads JSON from filtered_reader,
@@ -181,7 +225,7 @@ async fn spawn_and_handshake(
     let timeout_secs = config.handshake_timeout_secs;
     let handshake_result = tokio::time::timeout(
         Duration::from_secs(timeout_secs),
-        ().serve_with_ct(transport, cancel.clone()),
+        PorterClient::new(config, list_changed).serve_with_ct(transport, cancel.clone()),
     )
     .await;
 
@@ -202,15 +246,28 @@ async fn spawn_and_handshake(
 ///
 /// Runs in a `tokio::spawn` task. Handles spawning, handshake, tool discovery,
 /// call forwarding, crash detection, restart with exponential backoff, and
-/// clean shutdown.
+/// clean shutdown. Nothing is spawned until every dependency is Healthy, and
+/// every spawn attempt first reserves a slot from the registry-wide `spawn_budget`.
 pub(crate) async fn run_stdio_server(
     config: ServerConfig,
     slug: String,
-    tools: Arc<RwLock<Vec<Tool>>>,
     call_rx: mpsc::Receiver<ToolCallRequest>,
     health_tx: watch::Sender<HealthState>,
+    spawn_budget: Arc<SpawnBudget>,
+    shared: ServerShared,
     cancel: CancellationToken,
 ) {
+    let ServerShared {
+        tools,
+        status,
+        tools_changed,
+        list_changed,
+        dependencies,
+    } = shared;
+    if !wait_for_dependencies(&slug, dependencies, &cancel).await {
+        return;
+    }
+
     let stderr_buf = Arc::new(Mutex::new(StderrBuffer::new(100)));
     let call_rx = Arc::new(Mutex::new(call_rx));
 
@@ -221,11 +278,33 @@ pub(crate) async fn run_stdio_server(
         // --- Spawn and handshake ---
         let _ = health_tx.send(HealthState::Starting);
 
+        tokio::select! {
+            _ = spawn_budget.acquire() => {}
+            _ = cancel.cancelled() => {
+                tracing::info!(server = %slug, "cancelled while waiting for spawn budget");
+                return;
+            }
+        }
+
         tracing::info!(server = %slug, "spawning STDIO MCP server");
 
-        match spawn_and_handshake(&config, &slug, stderr_buf.clone(), cancel.clone()).await {
+        match spawn_and_handshake(
+            &config,
+            &slug,
+            stderr_buf.clone(),
+            list_changed.clone(),
+            status.clone(),
+            cancel.clone(),
+        )
+        .await
+        {
             Err(e) => {
                 tracing::warn!(server = %slug, error = %e, "server spawn/handshake failed");
+                if !e.is_retryable() {
+                    tracing::error!(server = %slug, error = %e, "non-retryable spawn error — marking Unhealthy");
+                    let _ = health_tx.send(HealthState::Unhealthy);
+                    return;
+                }
                 consecutive_failures += 1;
                 if consecutive_failures >= MAX_FAILURES {
                     tracing::error!(
@@ -237,19 +316,19 @@ pub(crate) async fn run_stdio_server(
                     return;
                 }
                 let _ = health_tx.send(HealthState::Degraded);
+                let delay = next_retry_delay(&config, consecutive_failures, &mut backoff);
                 tracing::info!(
                     server = %slug,
-                    backoff_secs = backoff.as_secs(),
+                    backoff_ms = delay.as_millis() as u64,
                     "backing off before restart"
                 );
                 tokio::select! {
-                    _ = tokio::time::sleep(backoff) => {}
+                    _ = tokio::time::sleep(delay) => {}
                     _ = cancel.cancelled() => {
                         tracing::info!(server = %slug, "cancelled during backoff sleep");
                         return;
                     }
                 }
-                backoff = std::cmp::min(backoff * 2, BACKOFF_MAX);
                 continue;
             }
             Ok((running, mut child)) => {
@@ -257,25 +336,14 @@ pub(crate) async fn run_stdio_server(
                 // Clone peer before passing `running` to the waiting task (running.waiting() consumes self)
                 let peer: rmcp::Peer<RoleClient> = running.peer().clone();
 
-                match peer.list_all_tools().await {
-                    Ok(discovered_tools) => {
-                        let namespaced: Vec<Tool> = discovered_tools
-                            .into_iter()
-                            .map(|t: Tool| namespace_tool(&slug, t))
-                            .collect();
-                        let count = namespaced.len();
-                        *tools.write().await = namespaced;
-                        tracing::info!(server = %slug, tool_count = count, "tools discovered");
-                    }
-                    Err(e) => {
-                        tracing::warn!(server = %slug, error = %e, "failed to list tools after handshake");
-                    }
-                }
+                status.record_listing(discover_tools(&peer, &slug, &config, &tools).await);
+                check_protocol_version(&peer, &slug, &config, &status);
 
                 // Reset failure counters on successful connection
                 consecutive_failures = 0;
                 backoff = BACKOFF_INITIAL;
-                let _ = health_tx.send(HealthState::Healthy);
+                let mut error_tracker = ErrorRateTracker::new(Duration::from_secs(60));
+                let _ = health_tx.send(status.record(&error_tracker));
 
                 // --- Spawn a task to watch for process exit ---
                 let (exit_tx, mut exit_rx) = tokio::sync::oneshot::channel::<()>();
@@ -285,7 +353,8 @@ pub(crate) async fn run_stdio_server(
                 });
 
                 // --- Call-forwarding + exit detection loop ---
-                let mut error_tracker = ErrorRateTracker::new(Duration::from_secs(60));
+                let mut probe_ticker = health_probe_ticker(&config);
+                let probe_timeout = Duration::from_secs(config.health_check_interval_secs.max(1));
                 let exited_unexpectedly = loop {
                     let mut rx_guard = call_rx.lock().await;
                     tokio::select! {
@@ -299,18 +368,28 @@ pub(crate) async fn run_stdio_server(
                                     return;
                                 }
                                 Some(req) => {
-                                    let result = peer.call_tool(req.params).await
-                                        .map_err(|e: rmcp::ServiceError| PorterError::Protocol(slug.clone(), e.to_string()));
+                                    let result = forward_call(&peer, &slug, &config, req.params).await;
                                     match &result {
                                         Ok(_) => error_tracker.record_success(),
                                         Err(_) => error_tracker.record_error(),
                                     }
-                                    let new_health = error_tracker.health_state();
-                                    let _ = health_tx.send(new_health);
+                                    let _ = health_tx.send(status.record(&error_tracker));
                                     let _ = req.response_tx.send(result);
                                 }
                             }
                         }
+                        _ = list_changed.notified() => {
+                            drop(rx_guard);
+                            status.record_listing(refresh_tools(&peer, &slug, &config, &tools, &tools_changed).await);
+                            let _ = health_tx.send(status.record(&error_tracker));
+                        }
+                        _ = probe_ticker.tick(), if config.health_check_tool.is_some() => {
+                            drop(rx_guard);
+                            if let Some(tool) = &config.health_check_tool {
+                                run_health_probe(&peer, &slug, tool, probe_timeout, &mut error_tracker).await;
+                                let _ = health_tx.send(status.record(&error_tracker));
+                            }
+                        }
                         _ = &mut exit_rx => {
                             // Server process exited unexpectedly
                             drop(rx_guard);
@@ -328,7 +407,7 @@ pub(crate) async fn run_stdio_server(
                 if exited_unexpectedly {
                     tracing::warn!(server = %slug, "server process exited unexpectedly, restarting");
                     // Clear tools since server is down
-                    tools.write().await.clear();
+                    tools.store(Arc::new(Vec::new()));
                     consecutive_failures += 1;
                     if consecutive_failures >= MAX_FAILURES {
                         tracing::error!(
@@ -340,19 +419,19 @@ pub(crate) async fn run_stdio_server(
                         return;
                     }
                     let _ = health_tx.send(HealthState::Degraded);
+                    let delay = next_retry_delay(&config, consecutive_failures, &mut backoff);
                     tracing::info!(
                         server = %slug,
-                        backoff_secs = backoff.as_secs(),
+                        backoff_ms = delay.as_millis() as u64,
                         "backing off before restart"
                     );
                     tokio::select! {
-                        _ = tokio::time::sleep(backoff) => {}
+                        _ = tokio::time::sleep(delay) => {}
                         _ = cancel.cancelled() => {
                             tracing::info!(server = %slug, "cancelled during backoff sleep");
                             return;
                         }
                     }
-                    backoff = std::cmp::min(backoff * 2, BACKOFF_MAX);
                 }
             }
         }
@@ -363,26 +442,33 @@ pub(crate) async fn run_stdio_server(
 ///
 /// This is the primary entry point for Porter to start managing an external
 /// STDIO MCP server. The returned `ServerHandle` provides health monitoring,
-/// tool listing, and tool call routing.
+/// tool listing, and tool call routing. `spawn_budget` is shared across all
+/// STDIO servers in a registry to cap aggregate process spawns. The slug is
+/// sent on `tools_changed` whenever the server's tool list is re-listed. The
+/// process is not spawned until every one of `dependencies` is Healthy.
 pub fn spawn_stdio_server(
     config: ServerConfig,
     slug: String,
+    spawn_budget: Arc<SpawnBudget>,
+    tools_changed: broadcast::Sender<String>,
+    dependencies: Vec<Dependency>,
     cancel: CancellationToken,
 ) -> ServerHandle {
     let (health_tx, health_rx) = watch::channel(HealthState::Starting);
     let (call_tx, call_rx) = mpsc::channel(32);
-    let tools = Arc::new(RwLock::new(Vec::<Tool>::new()));
-
-    let tools_clone = tools.clone();
-    let slug_clone = slug.clone();
+    let shared = ServerShared::new(tools_changed, dependencies);
+    let tools = shared.tools.clone();
+    let status = shared.status.clone();
+    let flat_names = config.flat_names;
 
     tokio::spawn(run_stdio_server(
         config,
-        slug_clone,
-        tools_clone,
+        slug.clone(),
         call_rx,
         health_tx,
-        cancel,
+        spawn_budget,
+        shared,
+        cancel.clone(),
     ));
 
     ServerHandle {
@@ -390,27 +476,23 @@ pub fn spawn_stdio_server(
         health_rx,
         tools,
         call_tx,
+        cancel,
+        flat_names,
+        status,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{ServerConfig, TransportKind};
-    use std::collections::HashMap;
+    use crate::config::ServerConfig;
+    use crate::server::BACKOFF_MAX;
 
     fn make_stdio_config(slug: &str, command: Option<&str>) -> ServerConfig {
-        ServerConfig {
-            slug: slug.to_string(),
-            enabled: true,
-            transport: TransportKind::Stdio,
-            command: command.map(|s| s.to_string()),
-            args: vec![],
-            env: HashMap::new(),
-            cwd: None,
-            url: None,
-            handshake_timeout_secs: 30,
-        }
+        let mut config: ServerConfig =
+            toml::from_str(&format!("slug = \"{}\"\ntransport = \"stdio\"", slug)).unwrap();
+        config.command = command.map(str::to_string);
+        config
     }
 
     #[test]
@@ -434,6 +516,29 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_non_retryable_error_marks_unhealthy_without_backoff() {
+        use crate::server::budget::SpawnBudget;
+
+        // No command: InvalidConfig, which retrying cannot fix
+        let handle = spawn_stdio_server(
+            make_stdio_config("test", None),
+            "test".to_string(),
+            Arc::new(SpawnBudget::new(None, Duration::from_secs(60))),
+            broadcast::channel(1).0,
+            vec![],
+            CancellationToken::new(),
+        );
+        let mut health_rx = handle.health_rx.clone();
+        tokio::time::timeout(
+            BACKOFF_INITIAL / 2,
+            health_rx.wait_for(|h| *h == HealthState::Unhealthy),
+        )
+        .await
+        .expect("should be Unhealthy before the first backoff elapses")
+        .unwrap();
+    }
+
     #[tokio::test]
     async fn test_stdout_filter_passes_json_and_discards_non_json() {
         // Simulate child stdout with mixed lines
@@ -464,6 +569,132 @@ mod tests {
         drop(read_half);
     }
 
+    #[tokio::test]
+    async fn test_stdout_filter_counts_truncated_jsonrpc_as_framing_error() {
+        use tokio::io::AsyncReadExt;
+
+        let input: &[u8] = b"starting up...\n{\"jsonrpc\":\"2.0\",\"id\":1,\"res\n{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}\n";
+        let status = Arc::new(ServerStatus::default());
+        let mut reader = start_stdout_filter(
+            input,
+            "test".to_string(),
+            status.clone(),
+            CancellationToken::new(),
+        );
+
+        let mut forwarded = String::new();
+        reader.read_to_string(&mut forwarded).await.unwrap();
+        assert_eq!(forwarded, "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{}}\n");
+        assert_eq!(
+            status
+                .framing_errors
+                .load(std::sync::atomic::Ordering::Relaxed),
+            1
+        );
+    }
+
+    #[test]
+    fn test_classify_stdout_line() {
+        assert_eq!(classify_stdout_line("{\"x\":1}"), StdoutLine::Json);
+        assert_eq!(classify_stdout_line("INFO listening"), StdoutLine::Noise);
+        assert_eq!(classify_stdout_line("{not json}"), StdoutLine::Noise);
+        assert_eq!(
+            classify_stdout_line("{\"jsonrpc\":\"2.0\",\"method\":"),
+            StdoutLine::Malformed
+        );
+    }
+
+    /// Spawn a server whose process appends a line to a file and exits, and
+    /// count the spawns made within `window`.
+    async fn count_spawns(immediate_first_retry: bool, window: Duration) -> usize {
+        use crate::server::budget::SpawnBudget;
+
+        let spawns = tempfile::NamedTempFile::new().unwrap();
+        let mut config = make_stdio_config("test", Some("sh"));
+        config.args = vec![
+            "-c".to_string(),
+            format!("echo spawn >> {}", spawns.path().display()),
+        ];
+        config.immediate_first_retry = immediate_first_retry;
+        let handle = spawn_stdio_server(
+            config,
+            "test".to_string(),
+            Arc::new(SpawnBudget::unlimited()),
+            broadcast::channel(1).0,
+            vec![],
+            CancellationToken::new(),
+        );
+        tokio::time::sleep(window).await;
+        handle.cancel.cancel();
+        std::fs::read_to_string(spawns.path())
+            .unwrap()
+            .lines()
+            .count()
+    }
+
+    #[tokio::test]
+    async fn test_dependent_waits_for_dependency_to_be_healthy() {
+        use crate::server::budget::SpawnBudget;
+
+        let spawns = tempfile::NamedTempFile::new().unwrap();
+        let mut config = make_stdio_config("test", Some("sh"));
+        config.args = vec![
+            "-c".to_string(),
+            format!("echo spawn >> {}", spawns.path().display()),
+        ];
+        let (dep_tx, dep_rx) = watch::channel(HealthState::Starting);
+        let handle = spawn_stdio_server(
+            config,
+            "test".to_string(),
+            Arc::new(SpawnBudget::unlimited()),
+            broadcast::channel(1).0,
+            vec![("backend".to_string(), dep_rx)],
+            CancellationToken::new(),
+        );
+        let spawned = || {
+            std::fs::read_to_string(spawns.path())
+                .unwrap()
+                .lines()
+                .count()
+        };
+
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(
+            spawned(),
+            0,
+            "must not spawn before the dependency is Healthy"
+        );
+
+        dep_tx.send(HealthState::Healthy).unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        handle.cancel.cancel();
+        assert!(spawned() > 0, "should spawn once the dependency is Healthy");
+    }
+
+    #[tokio::test]
+    async fn test_immediate_first_retry_skips_initial_backoff() {
+        // Well inside the 1s initial backoff
+        let window = BACKOFF_INITIAL / 2;
+        assert_eq!(count_spawns(true, window).await, 2);
+        assert_eq!(count_spawns(false, window).await, 1);
+    }
+
+    #[test]
+    fn test_next_retry_delay() {
+        let mut config = make_stdio_config("test", Some("x"));
+        let mut backoff = BACKOFF_INITIAL;
+        assert_eq!(next_retry_delay(&config, 1, &mut backoff), Duration::ZERO);
+        assert_eq!(next_retry_delay(&config, 2, &mut backoff), BACKOFF_INITIAL);
+        assert_eq!(
+            next_retry_delay(&config, 3, &mut backoff),
+            BACKOFF_INITIAL * 2
+        );
+
+        config.immediate_first_retry = false;
+        let mut backoff = BACKOFF_INITIAL;
+        assert_eq!(next_retry_delay(&config, 1, &mut backoff), BACKOFF_INITIAL);
+    }
+
     #[test]
     fn test_backoff_cap_at_30s() {
         let mut backoff = BACKOFF_INITIAL;
@@ -0,0 +1,116 @@
+//! Authenticated admin routes for `porter serve`.
+//!
+//! `POST /admin/reload` reloads the config from disk and swaps the registry on
+//! demand, the same way a file-watch event does. It is useful where file
+//! watching is unreliable (network filesystems, some container mounts). The
+//! route requires `Authorization: Bearer <token>` matching `[admin].token`.
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::routing::post;
+
+use crate::standalone::hot_reload::ReloadTarget;
+
+#[derive(Clone)]
+struct AdminState {
+    target: ReloadTarget,
+    token: String,
+}
+
+/// Build the `/admin/reload` route, guarded by the shared-secret `token`.
+pub fn admin_routes(target: ReloadTarget, token: String) -> Router {
+    Router::new()
+        .route("/admin/reload", post(reload))
+        .with_state(AdminState { target, token })
+}
+
+async fn reload(State(state): State<AdminState>, headers: HeaderMap) -> (StatusCode, String) {
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if !presented.is_some_and(|t| constant_time_eq(t.as_bytes(), state.token.as_bytes())) {
+        tracing::warn!("rejected admin reload with missing or invalid token");
+        return (StatusCode::UNAUTHORIZED, "unauthorized".to_string());
+    }
+
+    match state.target.reload().await {
+        Ok(server_count) => {
+            tracing::info!(servers = server_count, "config reloaded via admin endpoint");
+            (StatusCode::OK, format!("reloaded {} servers", server_count))
+        }
+        Err(e) => {
+            tracing::warn!(error = %e, "admin reload failed, keeping previous config");
+            (StatusCode::UNPROCESSABLE_ENTITY, e.to_string())
+        }
+    }
+}
+
+/// Compare secrets without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PorterRegistry;
+    use axum::body::Body;
+    use axum::http::Request;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+    use tower::ServiceExt;
+
+    async fn post_reload(router: Router, token: Option<&str>) -> StatusCode {
+        let mut req = Request::builder().method("POST").uri("/admin/reload");
+        if let Some(token) = token {
+            req = req.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        router
+            .oneshot(req.body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn test_admin_reload_requires_token_and_swaps_registry() {
+        let mut config = tempfile::NamedTempFile::new().unwrap();
+        writeln!(config, "# empty porter.toml").unwrap();
+        let old = Arc::new(PorterRegistry::from_handles(HashMap::new()));
+        let target = ReloadTarget::new(
+            config.path().to_path_buf(),
+            vec![],
+            vec![],
+            Arc::new(RwLock::new(old.clone())),
+            Arc::new(tokio::sync::Mutex::new(Vec::new())),
+        );
+        let router = admin_routes(target.clone(), "s3cret".to_string());
+
+        assert_eq!(
+            post_reload(router.clone(), Some("wrong")).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert_eq!(
+            post_reload(router.clone(), None).await,
+            StatusCode::UNAUTHORIZED
+        );
+        assert!(Arc::ptr_eq(&*target.registry_handle.read().await, &old));
+
+        assert_eq!(post_reload(router, Some("s3cret")).await, StatusCode::OK);
+        assert!(
+            !Arc::ptr_eq(&*target.registry_handle.read().await, &old),
+            "registry should be swapped"
+        );
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"ab"));
+    }
+}
@@ -0,0 +1,121 @@
+//! ETag-style "not modified" results for repeated tool calls, enabled by the
+//! top-level `etags = true`.
+//!
+//! Every successful tool result carries a hash of its content under
+//! `_meta["porter/etag"]`. A client polling a read-only tool can send that
+//! value back as `_meta["porter/ifNoneMatch"]`; when the new result hashes the
+//! same, Porter replaces the payload with a compact not-modified marker. The
+//! backend is still called — only the bytes sent to the client are saved.
+//!
+//! Results with `structured_content` are left alone: the marker has none, so
+//! it would break a tool that declares an `outputSchema`.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+use rmcp::model::{CallToolResult, Content, Meta};
+
+/// `_meta` key on a result holding the hash of its content.
+pub const ETAG_META_KEY: &str = "porter/etag";
+
+/// `_meta` key a client sets to the ETag of the result it already has.
+pub const IF_NONE_MATCH_META_KEY: &str = "porter/ifNoneMatch";
+
+/// `_meta` key set to `true` on a not-modified result.
+pub const NOT_MODIFIED_META_KEY: &str = "porter/notModified";
+
+/// Hash of a result's content and structured content, as 16 hex digits.
+///
+/// The value is opaque to clients and only stable within one Porter build.
+fn result_etag(result: &CallToolResult) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(&result.content)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    serde_json::to_string(&result.structured_content)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Tag a successful result with its ETag, or collapse it to a not-modified
+/// marker when the ETag equals the client's `if_none_match`.
+///
+/// Error results and results with structured content pass through untouched.
+pub(crate) fn apply_etag(
+    mut result: CallToolResult,
+    if_none_match: Option<&str>,
+) -> CallToolResult {
+    if result.is_error == Some(true) || result.structured_content.is_some() {
+        return result;
+    }
+    let etag = result_etag(&result);
+    if if_none_match == Some(etag.as_str()) {
+        let mut meta = Meta::new();
+        meta.0.insert(NOT_MODIFIED_META_KEY.into(), true.into());
+        meta.0.insert(ETAG_META_KEY.into(), etag.into());
+        let mut marker = CallToolResult::success(vec![Content::text("not modified")]);
+        marker.meta = Some(meta);
+        return marker;
+    }
+    result
+        .meta
+        .get_or_insert_with(Meta::new)
+        .0
+        .insert(ETAG_META_KEY.into(), etag.into());
+    result
+}
+
+/// The client's `porter/ifNoneMatch` value, if it sent one.
+pub(crate) fn if_none_match(meta: Option<&Meta>) -> Option<String> {
+    meta?
+        .0
+        .get(IF_NONE_MATCH_META_KEY)?
+        .as_str()
+        .map(str::to_owned)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn etag_of(result: &CallToolResult) -> &str {
+        result.meta.as_ref().unwrap().0[ETAG_META_KEY]
+            .as_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_etag_tracks_content() {
+        let a = apply_etag(CallToolResult::success(vec![Content::text("a")]), None);
+        let a_again = apply_etag(CallToolResult::success(vec![Content::text("a")]), None);
+        let b = apply_etag(CallToolResult::success(vec![Content::text("b")]), None);
+        assert_eq!(etag_of(&a), etag_of(&a_again));
+        assert_ne!(etag_of(&a), etag_of(&b));
+        assert_eq!(a.content, vec![Content::text("a")]);
+    }
+
+    #[test]
+    fn test_stale_etag_returns_full_result() {
+        let result = apply_etag(
+            CallToolResult::success(vec![Content::text("fresh")]),
+            Some("0000000000000000"),
+        );
+        assert_eq!(result.content, vec![Content::text("fresh")]);
+        assert!(!result.meta.unwrap().0.contains_key(NOT_MODIFIED_META_KEY));
+    }
+
+    #[test]
+    fn test_error_results_are_not_tagged() {
+        let result = apply_etag(CallToolResult::error(vec![Content::text("boom")]), None);
+        assert!(result.meta.is_none());
+    }
+
+    #[test]
+    fn test_structured_results_are_not_tagged() {
+        let structured = || CallToolResult::structured(serde_json::json!({"count": 1}));
+        let etag = result_etag(&structured());
+        let result = apply_etag(structured(), Some(&etag));
+        assert!(result.meta.is_none());
+        assert_eq!(result.structured_content, structured().structured_content);
+    }
+}
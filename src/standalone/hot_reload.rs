@@ -1,10 +1,15 @@
 //! Hot-reload for `porter serve`.
 //!
-//! Watches the porter.toml config file using the `notify` crate. On each
+//! Watches the porter.toml config file (and any `--overlay` files layered over
+//! it) using the `notify` crate. On each
 //! detected change (with 100ms debounce), it re-parses the config and rebuilds
-//! the PorterRegistry. On success, the inner Arc<PorterRegistry> is swapped
-//! inside the outer Arc<RwLock<...>>, and all connected MCP client peers
-//! receive a tools-list-changed notification.
+//! the PorterRegistry. On success — after optionally waiting, bounded by
+//! `reload_ready_timeout_secs`, for the new servers to finish connecting — the
+//! inner Arc<PorterRegistry> is swapped inside the outer Arc<RwLock<...>>, and
+//! all connected MCP client peers receive a tools-list-changed notification.
+//!
+//! Managed servers that announce `tools/list_changed` are re-listed by their run
+//! loops; `spawn_tools_changed_relay` forwards those changes to the same peers.
 //!
 //! Stale peers (whose transport has closed) are pruned on notification error.
 //! On reload failure, the previous registry is preserved and a warning is logged.
@@ -17,24 +22,97 @@ use notify::RecursiveMode;
 use notify::Watcher;
 use rmcp::service::{Peer, RoleServer};
 use tokio::sync::RwLock;
+use tokio::sync::broadcast::error::RecvError;
 use tokio_util::sync::CancellationToken;
 
 use crate::PorterRegistry;
 use crate::config::PorterConfig;
 
-/// Watch `config_path` for changes and reload the registry on each change.
+/// Everything needed to rebuild the registry from disk and swap it in.
+///
+/// Shared by the file watcher and the on-demand `POST /admin/reload` route;
+/// clones share one reload lock, so their reloads never interleave.
+#[derive(Clone)]
+pub struct ReloadTarget {
+    /// Base porter.toml path
+    pub config_path: PathBuf,
+    /// Overlay files deep-merged over the base config, in order
+    pub overlays: Vec<PathBuf>,
+    /// If non-empty, each reloaded config is restricted to these slugs
+    pub only_slugs: Vec<String>,
+    /// Shared registry handle; inner Arc is swapped on reload
+    pub registry_handle: Arc<RwLock<Arc<PorterRegistry>>>,
+    /// Shared peers vec; tools-list-changed is sent to each peer
+    pub peers_handle: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
+    /// Held across load-and-swap so a reload always builds on the registry
+    /// the previous one swapped in.
+    reload_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl ReloadTarget {
+    /// Create a reload target with its own reload lock; clone it to share the
+    /// lock between the file watcher and `POST /admin/reload`.
+    pub fn new(
+        config_path: PathBuf,
+        overlays: Vec<PathBuf>,
+        only_slugs: Vec<String>,
+        registry_handle: Arc<RwLock<Arc<PorterRegistry>>>,
+        peers_handle: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
+    ) -> Self {
+        Self {
+            config_path,
+            overlays,
+            only_slugs,
+            registry_handle,
+            peers_handle,
+            reload_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Reload the config, swap in the new registry, and notify peers.
+    ///
+    /// The changes against the running config are logged as a `ConfigDiff`
+    /// (names only, no values). On error the previous registry is left in place. Returns the number of
+    /// servers in the new registry. Concurrent reloads through clones of this
+    /// target run one at a time.
+    pub async fn reload(&self) -> crate::Result<usize> {
+        let _reloading = self.reload_lock.lock().await;
+        let current = self.registry_handle.read().await.clone();
+        let (new_registry, ready_timeout) = reload_registry(
+            &self.config_path,
+            &self.overlays,
+            &self.only_slugs,
+            &current,
+        )
+        .await?;
+        let diff = current.config().diff(&new_registry.config());
+        tracing::info!(
+            settings = ?diff.settings,
+            added = ?diff.added,
+            removed = ?diff.removed,
+            changed = ?diff.changed,
+            "config diff"
+        );
+        let server_count = new_registry.server_count();
+        swap_registry(
+            &self.registry_handle,
+            &self.peers_handle,
+            new_registry,
+            ready_timeout,
+        )
+        .await;
+        Ok(server_count)
+    }
+}
+
+/// Watch `target`'s config file and overlays for changes and reload the
+/// registry on each change.
 ///
 /// # Arguments
-/// * `config_path` - Path to the porter.toml config file to watch
-/// * `registry_handle` - Shared registry handle; inner Arc is swapped on reload
-/// * `peers_handle` - Shared peers vec; tools-list-changed is sent to each peer
+/// * `target` - What to reload; pass a clone of the admin route's target so
+///   watcher and admin reloads are serialized
 /// * `cancel` - CancellationToken; function returns when cancelled
-pub async fn run_hot_reload(
-    config_path: PathBuf,
-    registry_handle: Arc<RwLock<Arc<PorterRegistry>>>,
-    peers_handle: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
-    cancel: CancellationToken,
-) {
+pub async fn run_hot_reload(target: ReloadTarget, cancel: CancellationToken) {
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<notify::Result<notify::Event>>();
 
     // Wrap the tokio sender in a closure — notify v8's EventHandler is implemented
@@ -52,13 +130,17 @@ pub async fn run_hot_reload(
         }
     };
 
-    if let Err(e) = watcher.watch(&config_path, RecursiveMode::NonRecursive) {
-        tracing::error!(
-            path = %config_path.display(),
-            error = %e,
-            "failed to watch config file for hot-reload"
-        );
-        return;
+    let config_path = &target.config_path;
+
+    for path in std::iter::once(config_path).chain(&target.overlays) {
+        if let Err(e) = watcher.watch(path, RecursiveMode::NonRecursive) {
+            tracing::error!(
+                path = %path.display(),
+                error = %e,
+                "failed to watch config file for hot-reload"
+            );
+            return;
+        }
     }
 
     // IMPORTANT: Keep watcher alive for the duration of this task (Pitfall 2 from research).
@@ -80,23 +162,13 @@ pub async fn run_hot_reload(
                         // Drain any remaining events that arrived during the sleep
                         while rx.try_recv().is_ok() {}
 
-                        match reload_registry(&config_path).await {
-                            Ok(new_registry) => {
-                                let tool_count = new_registry.server_count();
-                                // Swap the inner registry under write lock, then shut
-                                // down the old one to cancel its server tasks.
-                                let old_registry = {
-                                    let mut guard = registry_handle.write().await;
-                                    std::mem::replace(&mut *guard, Arc::new(new_registry))
-                                };
-                                old_registry.shutdown().await;
+                        match target.reload().await {
+                            Ok(tool_count) => {
                                 tracing::info!(
                                     tools = %tool_count,
                                     path = %config_path.display(),
                                     "config reloaded"
                                 );
-                                // Notify all connected peers; prune stale ones on error
-                                notify_peers(&peers_handle).await;
                             }
                             Err(e) => {
                                 tracing::warn!(
@@ -124,6 +196,62 @@ pub async fn run_hot_reload(
     }
 }
 
+/// Swap `new_registry` in, shut down the old one, and notify connected peers.
+///
+/// With a `ready_timeout`, first waits (bounded) for the new registry's servers
+/// to finish connecting, so clients re-listing tools see the complete set. The
+/// previous registry keeps serving in the meantime.
+async fn swap_registry(
+    registry_handle: &RwLock<Arc<PorterRegistry>>,
+    peers_handle: &Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
+    new_registry: PorterRegistry,
+    ready_timeout: Option<Duration>,
+) {
+    if let Some(timeout) = ready_timeout
+        && !new_registry.wait_until_ready(timeout).await
+    {
+        tracing::warn!(
+            timeout_secs = timeout.as_secs(),
+            "reloaded servers not ready within reload_ready_timeout_secs, swapping anyway"
+        );
+    }
+    spawn_tools_changed_relay(&new_registry, peers_handle.clone());
+    // Swap the inner registry under write lock, then shut down the old one to
+    // cancel its server tasks.
+    let old_registry = {
+        let mut guard = registry_handle.write().await;
+        std::mem::replace(&mut *guard, Arc::new(new_registry))
+    };
+    old_registry.shutdown().await;
+    // Notify all connected peers; prune stale ones on error
+    notify_peers(peers_handle).await;
+}
+
+/// Forward `registry`'s runtime tool-list changes to all connected peers.
+///
+/// The relay ends on its own once the registry is shut down and dropped, so
+/// each registry swapped in by a reload gets its own relay.
+pub fn spawn_tools_changed_relay(
+    registry: &PorterRegistry,
+    peers_handle: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
+) {
+    let mut rx = registry.subscribe_tools_changed();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(slug) => {
+                    tracing::info!(server = %slug, "server tools changed, notifying clients");
+                }
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::debug!(missed, "tools-changed relay lagged, notifying clients");
+                }
+                Err(RecvError::Closed) => return,
+            }
+            notify_peers(&peers_handle).await;
+        }
+    });
+}
+
 /// Notify all connected peers of a tools-list-changed event.
 ///
 /// Peers that fail with a transport error are pruned from the vec.
@@ -143,29 +271,45 @@ async fn notify_peers(peers_handle: &Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>
     *peers = live_peers;
 }
 
-/// Load and parse the porter.toml config file, then build a new PorterRegistry.
-async fn reload_registry(config_path: &Path) -> crate::Result<PorterRegistry> {
-    let content = tokio::fs::read_to_string(config_path)
-        .await
-        .map_err(|e| crate::PorterError::InvalidConfig("hot-reload".into(), e.to_string()))?;
-    let config: PorterConfig = toml::from_str(&content)
-        .map_err(|e| crate::PorterError::InvalidConfig("hot-reload".into(), e.to_string()))?;
-    PorterRegistry::from_config(config).await
+/// Load the config with `PorterConfig::load`, exactly as at startup, then
+/// build a new PorterRegistry.
+///
+/// `overlays` are deep-merged over the base config in order; `only_slugs`
+/// restricts the reloaded config the same way `--server` did at startup.
+/// The new registry inherits `current`'s spawn budget. Also returns the
+/// reloaded config's `reload_ready_timeout_secs`.
+async fn reload_registry(
+    config_path: &Path,
+    overlays: &[PathBuf],
+    only_slugs: &[String],
+    current: &PorterRegistry,
+) -> crate::Result<(PorterRegistry, Option<Duration>)> {
+    let config = PorterConfig::load(config_path, overlays, only_slugs).await?;
+    let ready_timeout = config.reload_ready_timeout_secs.map(Duration::from_secs);
+    Ok((
+        PorterRegistry::from_config_after(config, current).await?,
+        ready_timeout,
+    ))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use arc_swap::ArcSwap;
     use std::io::Write;
     use tempfile::NamedTempFile;
 
+    fn no_registry() -> PorterRegistry {
+        PorterRegistry::from_handles(std::collections::HashMap::new())
+    }
+
     #[tokio::test]
     async fn test_reload_registry_empty_config() {
         // Create a temp file with a minimal valid porter.toml
         let mut temp = NamedTempFile::new().expect("create temp file");
         writeln!(temp, "# empty porter.toml").expect("write to temp file");
 
-        let result = reload_registry(temp.path()).await;
+        let result = reload_registry(temp.path(), &[], &[], &no_registry()).await;
         assert!(
             result.is_ok(),
             "empty config should reload successfully: {}",
@@ -173,17 +317,43 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_reloads_through_clones_are_serialized() {
+        let mut temp = NamedTempFile::new().expect("create temp file");
+        writeln!(temp, "# empty porter.toml").expect("write to temp file");
+        let target = ReloadTarget::new(
+            temp.path().to_path_buf(),
+            vec![],
+            vec![],
+            Arc::new(RwLock::new(Arc::new(no_registry()))),
+            Arc::new(tokio::sync::Mutex::new(Vec::new())),
+        );
+
+        // A reload in progress elsewhere holds the shared lock
+        let in_progress = target.reload_lock.lock().await;
+        let other = target.clone();
+        let reload = tokio::spawn(async move { other.reload().await });
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            !reload.is_finished(),
+            "reload must wait for the one in progress"
+        );
+
+        drop(in_progress);
+        assert_eq!(reload.await.unwrap().unwrap(), 0);
+    }
+
     #[tokio::test]
     async fn test_reload_registry_invalid_toml() {
         let mut temp = NamedTempFile::new().expect("create temp file");
         writeln!(temp, "this is not valid toml {{{{").expect("write to temp file");
 
-        let result = reload_registry(temp.path()).await;
+        let result = reload_registry(temp.path(), &[], &[], &no_registry()).await;
         assert!(result.is_err(), "invalid TOML should fail to reload");
         let err_str = result.err().unwrap().to_string();
         assert!(
-            err_str.contains("hot-reload"),
-            "error should mention hot-reload: {}",
+            err_str.contains("failed to parse"),
+            "error should name the parse failure: {}",
             err_str
         );
     }
@@ -191,10 +361,91 @@ mod tests {
     #[tokio::test]
     async fn test_reload_registry_missing_file() {
         let path = PathBuf::from("/nonexistent/path/porter.toml");
-        let result = reload_registry(&path).await;
+        let result = reload_registry(&path, &[], &[], &no_registry()).await;
         assert!(result.is_err(), "missing file should fail to reload");
     }
 
+    #[tokio::test]
+    async fn test_reload_registry_applies_slug_filter() {
+        let mut temp = NamedTempFile::new().expect("create temp file");
+        writeln!(
+            temp,
+            r#"
+[servers.a]
+slug = "a"
+transport = "stdio"
+command = "echo"
+enabled = false
+
+[servers.b]
+slug = "b"
+transport = "stdio"
+command = "echo"
+enabled = false
+"#
+        )
+        .expect("write");
+
+        assert!(
+            reload_registry(temp.path(), &[], &["a".to_string()], &no_registry())
+                .await
+                .is_ok()
+        );
+        let result =
+            reload_registry(temp.path(), &[], &["missing".to_string()], &no_registry()).await;
+        assert!(result.is_err(), "unknown slug should fail reload");
+    }
+
+    #[tokio::test]
+    async fn test_swap_waits_for_new_registry_to_settle() {
+        use crate::server::ServerHandle;
+        use crate::server::health::HealthState;
+        use std::collections::HashMap;
+
+        let old = Arc::new(PorterRegistry::from_handles(HashMap::new()));
+        let registry_handle = Arc::new(RwLock::new(old.clone()));
+        let peers: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>> =
+            Arc::new(tokio::sync::Mutex::new(Vec::new()));
+
+        // New registry with one server still discovering its tools
+        let (health_tx, health_rx) = tokio::sync::watch::channel(HealthState::Starting);
+        let (call_tx, _call_rx) = tokio::sync::mpsc::channel(1);
+        let handle = ServerHandle {
+            slug: "gh".to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(vec![])),
+            call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        let new_registry =
+            PorterRegistry::from_handles(HashMap::from([("gh".to_string(), handle)]));
+
+        let swap = tokio::spawn({
+            let registry_handle = registry_handle.clone();
+            async move {
+                swap_registry(
+                    &registry_handle,
+                    &peers,
+                    new_registry,
+                    Some(Duration::from_secs(5)),
+                )
+                .await
+            }
+        });
+
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(
+            Arc::ptr_eq(&*registry_handle.read().await, &old),
+            "registry must not be swapped while discovery is in progress"
+        );
+
+        health_tx.send(HealthState::Healthy).unwrap();
+        swap.await.unwrap();
+        assert_eq!(registry_handle.read().await.server_count(), 1);
+    }
+
     #[tokio::test]
     async fn test_notify_peers_empty_vec() {
         // Should not panic on empty peers vec
@@ -221,14 +472,15 @@ enabled = false
         )
         .expect("write");
 
-        let result = reload_registry(temp.path()).await;
+        let result = reload_registry(temp.path(), &[], &[], &no_registry()).await;
         assert!(
             result.is_ok(),
             "valid config with disabled server should load: {}",
             result.err().map(|e| e.to_string()).unwrap_or_default()
         );
         // Disabled server is not in the registry
-        let registry = result.unwrap();
+        let (registry, ready_timeout) = result.unwrap();
+        assert_eq!(ready_timeout, None);
         assert_eq!(
             registry.server_count(),
             0,
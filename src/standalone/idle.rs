@@ -0,0 +1,176 @@
+//! Idle shutdown for `porter serve`.
+//!
+//! `PorterMcpServer` touches an `ActivityTracker` on every MCP request and
+//! session initialization. `run_idle_shutdown` watches the tracker and cancels
+//! the root token once no activity has been seen for the configured duration
+//! and no Streamable HTTP session is open, letting on-demand deployments exit
+//! when nobody is using them.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use tokio::time::Instant;
+use tokio_util::sync::CancellationToken;
+
+/// Longest `run_idle_shutdown` sleeps before re-checking for open sessions.
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Records when the gateway last saw client activity.
+#[derive(Debug)]
+pub struct ActivityTracker {
+    last: Mutex<Instant>,
+}
+
+impl ActivityTracker {
+    /// Create a tracker whose idle clock starts now.
+    pub fn new() -> Self {
+        Self {
+            last: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Mark the gateway as active now.
+    pub fn touch(&self) {
+        *self.last.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+    }
+
+    /// Time elapsed since the last recorded activity.
+    pub fn idle_for(&self) -> Duration {
+        self.last
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed()
+    }
+}
+
+impl Default for ActivityTracker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Cancel `cancel` once `tracker` has been idle for at least `idle_timeout`.
+///
+/// An open session in `sessions` counts as activity, so a connected client
+/// keeps the gateway up even without recent requests. Returns when the
+/// shutdown fires or when `cancel` is cancelled elsewhere.
+pub async fn run_idle_shutdown(
+    tracker: Arc<ActivityTracker>,
+    sessions: Arc<LocalSessionManager>,
+    idle_timeout: Duration,
+    cancel: CancellationToken,
+) {
+    loop {
+        if !sessions.sessions.read().await.is_empty() {
+            tracker.touch();
+        }
+        let idle = tracker.idle_for();
+        if idle >= idle_timeout {
+            tracing::info!(
+                idle_secs = idle.as_secs(),
+                "no activity within idle timeout, shutting down"
+            );
+            cancel.cancel();
+            return;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep((idle_timeout - idle).min(IDLE_POLL_INTERVAL)) => {}
+            _ = cancel.cancelled() => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::transport::streamable_http_server::SessionManager;
+
+    fn no_sessions() -> Arc<LocalSessionManager> {
+        Arc::new(LocalSessionManager::default())
+    }
+
+    #[tokio::test]
+    async fn test_idle_shutdown_fires_after_inactivity() {
+        let tracker = Arc::new(ActivityTracker::new());
+        let cancel = CancellationToken::new();
+        let started = Instant::now();
+
+        run_idle_shutdown(
+            tracker,
+            no_sessions(),
+            Duration::from_millis(100),
+            cancel.clone(),
+        )
+        .await;
+
+        assert!(cancel.is_cancelled());
+        assert!(started.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn test_activity_postpones_shutdown() {
+        let tracker = Arc::new(ActivityTracker::new());
+        let cancel = CancellationToken::new();
+        let task = tokio::spawn(run_idle_shutdown(
+            tracker.clone(),
+            no_sessions(),
+            Duration::from_millis(200),
+            cancel.clone(),
+        ));
+
+        for _ in 0..3 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            tracker.touch();
+        }
+        assert!(
+            !cancel.is_cancelled(),
+            "activity should keep the gateway up"
+        );
+
+        task.await.unwrap();
+        assert!(cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_open_session_postpones_shutdown() {
+        let tracker = Arc::new(ActivityTracker::new());
+        let sessions = no_sessions();
+        let (session_id, _transport) = sessions.create_session().await.unwrap();
+        let cancel = CancellationToken::new();
+        let task = tokio::spawn(run_idle_shutdown(
+            tracker,
+            sessions.clone(),
+            Duration::from_millis(100),
+            cancel.clone(),
+        ));
+
+        // No requests at all, but the session stays open well past the window
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(
+            !cancel.is_cancelled(),
+            "an open session should keep the gateway up"
+        );
+
+        sessions.close_session(&session_id).await.unwrap();
+        tokio::time::timeout(Duration::from_secs(2), task)
+            .await
+            .expect("shutdown should fire once the session has closed")
+            .unwrap();
+        assert!(cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_external_cancel_stops_watcher() {
+        let tracker = Arc::new(ActivityTracker::new());
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        // Returns immediately without waiting out the timeout
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            run_idle_shutdown(tracker, no_sessions(), Duration::from_secs(3600), cancel),
+        )
+        .await
+        .expect("watcher should exit on cancel");
+    }
+}
@@ -1,2 +1,9 @@
+pub mod admin;
+pub mod etag;
 pub mod hot_reload;
+pub mod idle;
+pub mod once;
+pub mod probes;
 pub mod server;
+pub mod sessions;
+pub mod trace;
@@ -0,0 +1,167 @@
+//! One-shot mode for `porter stdio --once` and `porter serve --once`.
+//!
+//! Scripted use spawns Porter, runs one client session and expects the process
+//! to exit afterwards. `serve_once` serves a single session on a transport and
+//! then shuts down the registry's backend servers; `run_once_shutdown` watches
+//! a Streamable HTTP session manager and cancels the root token once the first
+//! session has been opened and closed again.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rmcp::service::{RoleServer, ServiceExt};
+use rmcp::transport::IntoTransport;
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use tokio_util::sync::CancellationToken;
+
+use crate::error::PorterError;
+use crate::standalone::server::PorterMcpServer;
+
+/// How often `run_once_shutdown` checks the session count.
+const ONCE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Serve exactly one MCP session on `transport`, then shut down.
+///
+/// Returns once the session's transport closes (or `cancel` fires), after
+/// shutting down every backend server in the current registry and cancelling
+/// `cancel`, so no child processes outlive the session.
+pub async fn serve_once<T, E, A>(
+    server: PorterMcpServer,
+    transport: T,
+    cancel: CancellationToken,
+) -> crate::Result<()>
+where
+    T: IntoTransport<RoleServer, E, A>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let registry = server.registry_handle();
+    let served = match server.serve_with_ct(transport, cancel.clone()).await {
+        Ok(running) => running
+            .waiting()
+            .await
+            .map(|reason| tracing::info!(?reason, "single MCP session ended, shutting down"))
+            .map_err(|e| PorterError::Protocol("porter".to_string(), e.to_string())),
+        Err(e) => Err(PorterError::Protocol("porter".to_string(), e.to_string())),
+    };
+
+    registry.read().await.shutdown().await;
+    cancel.cancel();
+    served
+}
+
+/// Cancel `cancel` once a session has been opened on `sessions` and all
+/// sessions have closed again.
+///
+/// Returns when the shutdown fires or when `cancel` is cancelled elsewhere.
+pub async fn run_once_shutdown(sessions: Arc<LocalSessionManager>, cancel: CancellationToken) {
+    let mut opened = false;
+    loop {
+        let active = sessions.sessions.read().await.len();
+        if active > 0 {
+            opened = true;
+        } else if opened {
+            tracing::info!("single MCP session closed, shutting down");
+            cancel.cancel();
+            return;
+        }
+        tokio::select! {
+            _ = tokio::time::sleep(ONCE_POLL_INTERVAL) => {}
+            _ = cancel.cancelled() => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PorterRegistry;
+    use std::collections::HashMap;
+
+    fn empty_server() -> PorterMcpServer {
+        PorterMcpServer::new(PorterRegistry::from_handles(HashMap::new()))
+    }
+
+    #[tokio::test]
+    async fn test_serve_once_exits_when_transport_closes() {
+        let (server_io, client_io) = tokio::io::duplex(4096);
+        let cancel = CancellationToken::new();
+        let serving = tokio::spawn(serve_once(empty_server(), server_io, cancel.clone()));
+
+        let client = ().serve(client_io).await.expect("client connects");
+        client.list_tools(None).await.expect("session is live");
+        assert!(!serving.is_finished());
+
+        // Closing the client side ends the session and the server with it
+        client.cancel().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), serving)
+            .await
+            .expect("serve_once should return after the session ends")
+            .unwrap()
+            .unwrap();
+        assert!(cancel.is_cancelled());
+    }
+
+    #[tokio::test]
+    async fn test_once_shutdown_after_http_session_closes() {
+        use axum::body::Body;
+        use axum::http::Request;
+        use rmcp::transport::streamable_http_server::{
+            StreamableHttpServerConfig, StreamableHttpService,
+        };
+        use tower::ServiceExt as _;
+
+        let sessions = Arc::new(LocalSessionManager::default());
+        let server = empty_server();
+        let service = StreamableHttpService::new(
+            move || Ok(server.clone()),
+            sessions.clone(),
+            StreamableHttpServerConfig::default(),
+        );
+        let cancel = CancellationToken::new();
+        let watcher = tokio::spawn(run_once_shutdown(sessions, cancel.clone()));
+
+        // An empty manager before any session has opened is not "done"
+        tokio::time::sleep(ONCE_POLL_INTERVAL * 2).await;
+        assert!(!cancel.is_cancelled());
+
+        let initialize = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "test", "version": "1.0"}
+            }
+        });
+        let response = service
+            .clone()
+            .oneshot(
+                Request::post("/mcp")
+                    .header("accept", "application/json, text/event-stream")
+                    .header("content-type", "application/json")
+                    .body(Body::from(initialize.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let session_id = response.headers()["mcp-session-id"].clone();
+        tokio::time::sleep(ONCE_POLL_INTERVAL * 2).await;
+        assert!(!cancel.is_cancelled(), "session is still open");
+
+        service
+            .oneshot(
+                Request::delete("/mcp")
+                    .header("mcp-session-id", session_id)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(5), watcher)
+            .await
+            .expect("shutdown should fire after the session closes")
+            .unwrap();
+        assert!(cancel.is_cancelled());
+    }
+}
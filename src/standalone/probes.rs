@@ -0,0 +1,217 @@
+//! Liveness and readiness probe routes for `porter serve`.
+//!
+//! `/healthz` reports liveness: it answers 200 as long as the process can
+//! serve HTTP, with each server's health snapshot (state, protocol version,
+//! degraded reason) as the JSON body. `/readyz` reports readiness: it answers 200 only once every
+//! managed server has finished starting (see `PorterRegistry::is_ready`), and
+//! 503 otherwise. This mirrors the Kubernetes liveness/readiness split.
+
+use std::sync::Arc;
+
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use tokio::sync::RwLock;
+
+use crate::PorterRegistry;
+
+/// Build the `/healthz` and `/readyz` routes backed by the shared registry handle.
+///
+/// The handle is the same double-Arc used by `PorterMcpServer`, so readiness
+/// follows hot-reload swaps automatically.
+pub fn probe_routes(registry: Arc<RwLock<Arc<PorterRegistry>>>) -> Router {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .with_state(registry)
+}
+
+async fn healthz(
+    State(registry): State<Arc<RwLock<Arc<PorterRegistry>>>>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let servers = registry.read().await.server_snapshots();
+    (
+        StatusCode::OK,
+        Json(serde_json::json!({ "status": "ok", "servers": servers })),
+    )
+}
+
+async fn readyz(
+    State(registry): State<Arc<RwLock<Arc<PorterRegistry>>>>,
+) -> (StatusCode, &'static str) {
+    if registry.read().await.is_ready() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not ready")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::ServerHandle;
+    use crate::server::health::HealthState;
+    use arc_swap::ArcSwap;
+    use axum::body::Body;
+    use axum::http::Request;
+    use rmcp::model::Tool;
+    use std::collections::HashMap;
+    use tokio::sync::{mpsc, watch};
+    use tokio_util::sync::CancellationToken;
+    use tower::ServiceExt;
+
+    fn mock_handle(slug: &str, health: HealthState) -> (ServerHandle, watch::Sender<HealthState>) {
+        let (health_tx, health_rx) = watch::channel(health);
+        let (call_tx, _call_rx) = mpsc::channel(1);
+        let handle = ServerHandle {
+            slug: slug.to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(Vec::<Tool>::new())),
+            call_tx,
+            cancel: CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        };
+        (handle, health_tx)
+    }
+
+    /// Backend whose every tool call succeeds.
+    #[derive(Clone)]
+    struct OkServer;
+
+    impl rmcp::ServerHandler for OkServer {
+        fn get_info(&self) -> rmcp::model::ServerInfo {
+            rmcp::model::ServerInfo {
+                capabilities: rmcp::model::ServerCapabilities::builder()
+                    .enable_tools()
+                    .build(),
+                ..Default::default()
+            }
+        }
+
+        async fn call_tool(
+            &self,
+            _request: rmcp::model::CallToolRequestParams,
+            _context: rmcp::service::RequestContext<rmcp::RoleServer>,
+        ) -> Result<rmcp::model::CallToolResult, rmcp::ErrorData> {
+            Ok(rmcp::model::CallToolResult::success(vec![
+                rmcp::model::Content::text("ok"),
+            ]))
+        }
+    }
+
+    async fn get_status(router: Router, path: &str) -> StatusCode {
+        let req = Request::builder().uri(path).body(Body::empty()).unwrap();
+        router.oneshot(req).await.unwrap().status()
+    }
+
+    async fn get_json(router: Router, path: &str) -> serde_json::Value {
+        let req = Request::builder().uri(path).body(Body::empty()).unwrap();
+        let body = router.oneshot(req).await.unwrap().into_body();
+        let bytes = axum::body::to_bytes(body, usize::MAX).await.unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    fn http_config(url: &str, extra: &str) -> crate::PorterConfig {
+        let mut config = crate::PorterConfig::default();
+        config.servers.insert(
+            "mock".to_string(),
+            toml::from_str(&format!(
+                "slug = \"mock\"\ntransport = \"http\"\nurl = \"{}\"\n{}",
+                url, extra
+            ))
+            .unwrap(),
+        );
+        config
+    }
+
+    #[tokio::test]
+    async fn test_healthz_always_ok() {
+        let (handle, _tx) = mock_handle("gh", HealthState::Starting);
+        let mut servers = HashMap::new();
+        servers.insert("gh".to_string(), handle);
+        let registry = Arc::new(RwLock::new(Arc::new(PorterRegistry::from_handles(servers))));
+        assert_eq!(
+            get_status(probe_routes(registry), "/healthz").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readyz_tracks_server_startup() {
+        let (handle, health_tx) = mock_handle("gh", HealthState::Starting);
+        let mut servers = HashMap::new();
+        servers.insert("gh".to_string(), handle);
+        let registry = Arc::new(RwLock::new(Arc::new(PorterRegistry::from_handles(servers))));
+
+        assert_eq!(
+            get_status(probe_routes(registry.clone()), "/readyz").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+
+        health_tx.send(HealthState::Healthy).unwrap();
+        assert_eq!(
+            get_status(probe_routes(registry), "/readyz").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_readyz_stays_ready_after_a_call() {
+        let url = crate::server::http::serve_mock_http(OkServer).await;
+        let config = http_config(&url, "");
+        let registry = Arc::new(PorterRegistry::from_config(config).await.unwrap());
+        assert!(
+            registry
+                .wait_until_ready(std::time::Duration::from_secs(10))
+                .await
+        );
+
+        // One call leaves far fewer samples than the error-rate window needs
+        registry.call_tool("mock__echo", None, None).await.unwrap();
+        assert_eq!(registry.server_health("mock"), Some(HealthState::Healthy));
+        let handle = Arc::new(RwLock::new(registry.clone()));
+        assert_eq!(
+            get_status(probe_routes(handle), "/readyz").await,
+            StatusCode::OK
+        );
+        registry.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn test_healthz_reports_protocol_floor_after_calls() {
+        let url = crate::server::http::serve_mock_http(OkServer).await;
+        let config = http_config(&url, "min_protocol_version = \"2999-01-01\"");
+        let registry = Arc::new(PorterRegistry::from_config(config).await.unwrap());
+        assert!(
+            registry
+                .wait_until_ready(std::time::Duration::from_secs(10))
+                .await
+        );
+
+        // Enough clean calls to fill the error-rate window stay Degraded
+        for _ in 0..6 {
+            registry.call_tool("mock__echo", None, None).await.unwrap();
+        }
+        assert_eq!(registry.server_health("mock"), Some(HealthState::Degraded));
+
+        let handle = Arc::new(RwLock::new(registry.clone()));
+        let body = get_json(probe_routes(handle), "/healthz").await;
+        let server = &body["servers"]["mock"];
+        assert_eq!(server["health"], "degraded");
+        assert_eq!(
+            server["protocol_version"],
+            rmcp::model::ProtocolVersion::default().to_string()
+        );
+        assert!(
+            server["degraded_reason"]
+                .as_str()
+                .unwrap()
+                .contains("min_protocol_version"),
+            "{}",
+            body
+        );
+        registry.shutdown().await;
+    }
+}
@@ -8,19 +8,122 @@
 //!
 //! Connected MCP client peers are stored in a shared Vec so the hot-reload task
 //! can broadcast tools-list-changed notifications after each reload.
+//!
+//! Porter's own operational state is exposed as read-only MCP resources:
+//! `porter://health` (per-server health snapshot), `porter://tools` (tool → server) and
+//! `porter://metrics` (per-server call payload sizes).
+//!
+//! A built-in `porter__info` tool is always listed and answered here, before
+//! registry routing, so clients can see the running version and overall health.
+//! Tools outside the config's `call_allowlist` are hidden from listings and
+//! refused before routing. With `etags = true`, routed results carry an ETag
+//! (see `standalone::etag`).
+//!
+//! With call tracing enabled, each `tools/list` and `tools/call` is logged as a
+//! request/response pair (see `standalone::trace`). Independently, both run
+//! inside `porter.list_tools` / `porter.call_tool` spans carrying the server,
+//! tool, outcome and duration, for export by an OpenTelemetry tracing layer.
 
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 
 use rmcp::ErrorData as McpError;
 use rmcp::handler::server::ServerHandler;
 use rmcp::model::{
-    CallToolRequestParams, CallToolResult, ListToolsResult, PaginatedRequestParams,
-    ServerCapabilities, ServerInfo,
+    AnnotateAble, CallToolRequestParams, CallToolResult, JsonObject, ListResourcesResult,
+    ListToolsResult, Meta, PaginatedRequestParams, RawResource, ReadResourceRequestParams,
+    ReadResourceResult, ResourceContents, ServerCapabilities, ServerInfo, Tool,
 };
 use rmcp::service::{NotificationContext, Peer, RequestContext, RoleServer};
 use tokio::sync::RwLock;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
 
 use crate::PorterRegistry;
+use crate::server::call_outcome;
+use crate::standalone::etag::{apply_etag, if_none_match};
+use crate::standalone::idle::ActivityTracker;
+use crate::standalone::trace::{CALL_TRACE_TARGET, CallTracer, redact_json};
+
+/// Resource URI for the JSON map of server slug → health snapshot (state,
+/// protocol version, degraded reason).
+pub const HEALTH_RESOURCE_URI: &str = "porter://health";
+
+/// Resource URI for the JSON map of exposed tool name → owning server slug.
+pub const TOOLS_RESOURCE_URI: &str = "porter://tools";
+
+/// Resource URI for the JSON map of server slug → cumulative request/response bytes.
+pub const METRICS_RESOURCE_URI: &str = "porter://metrics";
+
+/// Name of the built-in tool reporting Porter's version, config and health.
+///
+/// `porter` is a reserved server slug, so no backend tool can share this name.
+pub const INFO_TOOL_NAME: &str = "porter__info";
+
+/// Definition of the built-in `porter__info` tool (no arguments).
+fn info_tool() -> Tool {
+    let schema = serde_json::json!({"type": "object", "properties": {}});
+    let schema = match schema {
+        serde_json::Value::Object(map) => map,
+        _ => JsonObject::new(),
+    };
+    Tool::new(
+        INFO_TOOL_NAME,
+        "[via porter] Porter's version, loaded config path, server count and aggregate health",
+        Arc::new(schema),
+    )
+}
+
+/// Answer `porter__info` from the current registry.
+fn porter_info(registry: &PorterRegistry, config_path: Option<&PathBuf>) -> CallToolResult {
+    let mut health: BTreeMap<String, usize> = BTreeMap::new();
+    for state in registry.all_server_health().into_values() {
+        if let Ok(serde_json::Value::String(name)) = serde_json::to_value(state) {
+            *health.entry(name).or_default() += 1;
+        }
+    }
+    CallToolResult::structured(serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "config_path": config_path.map(|p| p.display().to_string()),
+        "server_count": registry.server_count(),
+        "ready": registry.is_ready(),
+        "health": health,
+        "servers": registry.server_snapshots(),
+    }))
+}
+
+/// Render one of Porter's introspection resources from the current registry.
+async fn read_porter_resource(
+    registry: &PorterRegistry,
+    uri: &str,
+) -> Result<ReadResourceResult, McpError> {
+    let json = match uri {
+        HEALTH_RESOURCE_URI => serde_json::to_string_pretty(&registry.server_snapshots()),
+        TOOLS_RESOURCE_URI => serde_json::to_string_pretty(&registry.tool_sources().await),
+        METRICS_RESOURCE_URI => {
+            let traffic: BTreeMap<_, _> = registry.all_server_traffic().into_iter().collect();
+            serde_json::to_string_pretty(&traffic)
+        }
+        _ => {
+            return Err(McpError::resource_not_found(
+                format!("unknown resource '{}'", uri),
+                None,
+            ));
+        }
+    }
+    .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+
+    Ok(ReadResourceResult {
+        contents: vec![ResourceContents::TextResourceContents {
+            uri: uri.to_string(),
+            mime_type: Some("application/json".into()),
+            text: json,
+            meta: None,
+        }],
+    })
+}
 
 /// MCP server backed by a PorterRegistry.
 ///
@@ -40,6 +143,12 @@ pub struct PorterMcpServer {
     /// Connected session peers for broadcasting tools-list-changed notifications.
     /// Stale peers are pruned on notification error.
     peers: Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>>,
+    /// Last client activity (any MCP request or session init), for idle shutdown.
+    activity: Arc<ActivityTracker>,
+    /// Correlation IDs for `--trace-calls`; `None` when call tracing is off.
+    tracer: Option<Arc<CallTracer>>,
+    /// Config file the registry was loaded from, reported by `porter__info`.
+    config_path: Option<Arc<PathBuf>>,
 }
 
 impl PorterMcpServer {
@@ -48,9 +157,25 @@ impl PorterMcpServer {
         Self {
             registry: Arc::new(RwLock::new(Arc::new(registry))),
             peers: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            activity: Arc::new(ActivityTracker::new()),
+            tracer: None,
+            config_path: None,
         }
     }
 
+    /// Record the config file path reported by the `porter__info` tool.
+    pub fn with_config_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(Arc::new(path.into()));
+        self
+    }
+
+    /// Log every `tools/list` and `tools/call` request and its (redacted)
+    /// result at info level under `CALL_TRACE_TARGET`.
+    pub fn with_call_tracing(mut self) -> Self {
+        self.tracer = Some(Arc::new(CallTracer::default()));
+        self
+    }
+
     /// Return the registry handle for the hot-reload task to swap the inner registry.
     pub fn registry_handle(&self) -> Arc<RwLock<Arc<PorterRegistry>>> {
         self.registry.clone()
@@ -60,6 +185,152 @@ impl PorterMcpServer {
     pub fn peers_handle(&self) -> Arc<tokio::sync::Mutex<Vec<Peer<RoleServer>>>> {
         self.peers.clone()
     }
+
+    /// Return the activity tracker shared by all sessions, for idle shutdown.
+    pub fn activity_handle(&self) -> Arc<ActivityTracker> {
+        self.activity.clone()
+    }
+
+    /// Snapshot the current registry without holding the lock.
+    ///
+    /// Handlers must not keep the read guard across a backend call: a slow call
+    /// would otherwise block hot-reload's write lock, and with tokio's fair
+    /// `RwLock` every request queued behind that writer as well.
+    async fn current_registry(&self) -> Arc<PorterRegistry> {
+        self.registry.read().await.clone()
+    }
+
+    /// List the current registry's tools, tracing the exchange if enabled.
+    async fn traced_list_tools(&self) -> Vec<Tool> {
+        let trace = self.tracer.as_ref().map(|tracer| {
+            let call_id = tracer.next_id();
+            tracing::info!(target: CALL_TRACE_TARGET, call_id, method = "tools/list", "request");
+            (call_id, Instant::now())
+        });
+        let span =
+            tracing::info_span!("porter.list_tools", tool_count = Empty, duration_ms = Empty);
+        let started = Instant::now();
+        let tools = async {
+            let registry = self.current_registry().await;
+            let mut tools = registry.tools().await;
+            tools.retain(|t| registry.is_call_allowed(&t.name));
+            tools.push(info_tool());
+            tools
+        }
+        .instrument(span.clone())
+        .await;
+        span.record("tool_count", tools.len());
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        if let Some((call_id, started)) = trace {
+            tracing::info!(
+                target: CALL_TRACE_TARGET,
+                call_id,
+                method = "tools/list",
+                elapsed_ms = started.elapsed().as_millis() as u64,
+                tool_count = tools.len(),
+                "response"
+            );
+        }
+        tools
+    }
+
+    /// Answer `porter__info`, refuse tools outside `call_allowlist`, or route the
+    /// call through the current registry, tagging the result with its ETag.
+    async fn route_call(
+        &self,
+        name: &str,
+        arguments: Option<JsonObject>,
+        meta: Option<Meta>,
+    ) -> Result<CallToolResult, McpError> {
+        let registry = self.current_registry().await;
+        if name == INFO_TOOL_NAME {
+            return Ok(porter_info(&registry, self.config_path.as_deref()));
+        }
+        if !registry.is_call_allowed(name) {
+            tracing::warn!(tool = %name, "refusing call to tool outside call_allowlist");
+            return Err(McpError::invalid_params(
+                format!("tool '{}' is not in call_allowlist", name),
+                None,
+            ));
+        }
+        if let Some(slug) = registry.tool_owner(name).await {
+            Span::current().record("server", slug.as_str());
+        }
+        let if_none_match = if_none_match(meta.as_ref());
+        let etags = registry.etags_enabled();
+        registry
+            .call_tool(name, arguments, meta)
+            .await
+            .map(|result| match etags {
+                true => apply_etag(result, if_none_match.as_deref()),
+                false => result,
+            })
+            .map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+
+    /// Route a tool call through the current registry, tracing the exchange if enabled.
+    ///
+    /// `porter__info` is answered locally and never reaches the registry; tools
+    /// outside `call_allowlist` are refused without reaching a backend.
+    async fn traced_call_tool(
+        &self,
+        name: &str,
+        arguments: Option<JsonObject>,
+        meta: Option<Meta>,
+    ) -> Result<CallToolResult, McpError> {
+        let trace = self.tracer.as_ref().map(|tracer| {
+            let call_id = tracer.next_id();
+            let args = serde_json::Value::Object(arguments.clone().unwrap_or_default());
+            tracing::info!(
+                target: CALL_TRACE_TARGET,
+                call_id,
+                method = "tools/call",
+                tool = %name,
+                arguments = %redact_json(&args),
+                "request"
+            );
+            (call_id, Instant::now())
+        });
+        let span = tracing::info_span!(
+            "porter.call_tool",
+            server = Empty,
+            tool = %name,
+            outcome = Empty,
+            duration_ms = Empty,
+        );
+        let started = Instant::now();
+        let result = self
+            .route_call(name, arguments, meta)
+            .instrument(span.clone())
+            .await;
+        span.record("outcome", call_outcome(&result));
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        if let Some((call_id, started)) = trace {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            match &result {
+                Ok(r) => tracing::info!(
+                    target: CALL_TRACE_TARGET,
+                    call_id,
+                    method = "tools/call",
+                    tool = %name,
+                    elapsed_ms,
+                    is_error = r.is_error.unwrap_or(false),
+                    result = %serde_json::to_value(r).map(|v| redact_json(&v)).unwrap_or_default(),
+                    "response"
+                ),
+                Err(e) => tracing::info!(
+                    target: CALL_TRACE_TARGET,
+                    call_id,
+                    method = "tools/call",
+                    tool = %name,
+                    elapsed_ms,
+                    error = %e.message,
+                    "response"
+                ),
+            }
+        }
+        result
+    }
 }
 
 impl ServerHandler for PorterMcpServer {
@@ -76,19 +347,22 @@ impl ServerHandler for PorterMcpServer {
             instructions: Some(
                 "Porter MCP Gateway — aggregates MCP servers as callable tools.".into(),
             ),
-            capabilities: ServerCapabilities::builder().enable_tools().build(),
+            capabilities: ServerCapabilities::builder()
+                .enable_tools()
+                .enable_resources()
+                .build(),
             ..Default::default()
         }
     }
 
-    /// List all tools from the registry (across all MCP servers).
+    /// List all tools from the registry (across all MCP servers), plus `porter__info`.
     async fn list_tools(
         &self,
         _request: Option<PaginatedRequestParams>,
         _context: RequestContext<RoleServer>,
     ) -> Result<ListToolsResult, McpError> {
-        let registry = self.registry.read().await;
-        let tools = registry.tools().await;
+        self.activity.touch();
+        let tools = self.traced_list_tools().await;
         Ok(ListToolsResult {
             tools,
             next_cursor: None,
@@ -100,13 +374,67 @@ impl ServerHandler for PorterMcpServer {
     async fn call_tool(
         &self,
         request: CallToolRequestParams,
-        _context: RequestContext<RoleServer>,
+        context: RequestContext<RoleServer>,
     ) -> Result<CallToolResult, McpError> {
-        let registry = self.registry.read().await;
-        registry
-            .call_tool(&request.name, request.arguments)
+        self.activity.touch();
+        // rmcp moves request-level `_meta` into the context; fold it back together
+        // with any params `_meta` so the backend sees what the client sent.
+        let mut meta = context.meta;
+        if let Some(params_meta) = request.meta {
+            meta.extend(params_meta);
+        }
+        let meta = (!meta.0.is_empty()).then_some(meta);
+
+        self.traced_call_tool(&request.name, request.arguments, meta)
             .await
-            .map_err(|e| McpError::internal_error(e.to_string(), None))
+    }
+
+    /// List Porter's introspection resources (`porter://health`, `porter://tools`,
+    /// `porter://metrics`).
+    async fn list_resources(
+        &self,
+        _request: Option<PaginatedRequestParams>,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ListResourcesResult, McpError> {
+        self.activity.touch();
+        let resource = |uri: &str, name: &str, description: &str| {
+            let mut raw = RawResource::new(uri, name);
+            raw.description = Some(description.into());
+            raw.mime_type = Some("application/json".into());
+            raw.no_annotation()
+        };
+        Ok(ListResourcesResult {
+            resources: vec![
+                resource(
+                    HEALTH_RESOURCE_URI,
+                    "health",
+                    "Health state, protocol version and degraded reason of every managed MCP server",
+                ),
+                resource(
+                    TOOLS_RESOURCE_URI,
+                    "tools",
+                    "Exposed tool names mapped to the server that provides them",
+                ),
+                resource(
+                    METRICS_RESOURCE_URI,
+                    "metrics",
+                    "Cumulative tool call request and response bytes per server",
+                ),
+            ],
+            next_cursor: None,
+            meta: None,
+        })
+    }
+
+    /// Read an introspection resource from the current registry.
+    async fn read_resource(
+        &self,
+        request: ReadResourceRequestParams,
+        _context: RequestContext<RoleServer>,
+    ) -> Result<ReadResourceResult, McpError> {
+        self.activity.touch();
+        let registry = self.current_registry().await;
+        read_porter_resource(&registry, &request.uri).await
     }
 
     /// Store the connected peer for later tools-list-changed notifications.
@@ -114,6 +442,7 @@ impl ServerHandler for PorterMcpServer {
     /// Called by rmcp after the client sends `InitializedNotification`. The peer
     /// is added to the shared peers vec so hot-reload can broadcast to all clients.
     async fn on_initialized(&self, context: NotificationContext<RoleServer>) {
+        self.activity.touch();
         tracing::info!("MCP client initialized, storing peer for hot-reload notifications");
         self.peers.lock().await.push(context.peer.clone());
     }
@@ -123,6 +452,7 @@ impl ServerHandler for PorterMcpServer {
 mod tests {
     use super::*;
     use crate::config::PorterConfig;
+    use arc_swap::ArcSwap;
     use std::collections::HashMap;
 
     /// Create a PorterMcpServer with an empty registry (no servers).
@@ -147,6 +477,446 @@ mod tests {
             "tools capability should be enabled"
         );
         assert!(info.instructions.is_some(), "instructions should be set");
+        assert!(
+            info.capabilities.resources.is_some(),
+            "resources capability should be enabled"
+        );
+    }
+
+    /// Extract the text body of a single-content resource read.
+    fn resource_text(result: ReadResourceResult) -> String {
+        match result.contents.into_iter().next() {
+            Some(ResourceContents::TextResourceContents { text, .. }) => text,
+            other => panic!("expected text contents, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_resource_is_valid_json() {
+        let mut config = PorterConfig::default();
+        config.servers.insert(
+            "gh".to_string(),
+            toml::from_str(
+                r#"
+                slug = "gh"
+                transport = "stdio"
+                command = "echo"
+                enabled = false
+                "#,
+            )
+            .unwrap(),
+        );
+        let registry = PorterRegistry::from_config(config).await.unwrap();
+        let text = resource_text(
+            read_porter_resource(&registry, HEALTH_RESOURCE_URI)
+                .await
+                .unwrap(),
+        );
+        let json: serde_json::Value = serde_json::from_str(&text).unwrap();
+        assert!(json.is_object(), "health resource should be a JSON object");
+
+        let tools = resource_text(
+            read_porter_resource(&registry, TOOLS_RESOURCE_URI)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&tools).unwrap(),
+            serde_json::json!({})
+        );
+
+        let metrics = resource_text(
+            read_porter_resource(&registry, METRICS_RESOURCE_URI)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&metrics).unwrap(),
+            serde_json::json!({})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_resource_is_not_found() {
+        let server = make_empty_server().await;
+        let registry = server.registry.read().await.clone();
+        let err = read_porter_resource(&registry, "porter://nope")
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, rmcp::model::ErrorCode::RESOURCE_NOT_FOUND);
+    }
+
+    /// Handle for a mock backend that answers every call after `delay`.
+    fn slow_handle(slug: &str, delay: std::time::Duration) -> crate::ServerHandle {
+        use crate::server::ToolCallRequest;
+        use rmcp::model::Content;
+
+        let (_health_tx, health_rx) = tokio::sync::watch::channel(crate::HealthState::Healthy);
+        let (call_tx, mut call_rx) = tokio::sync::mpsc::channel::<ToolCallRequest>(8);
+        tokio::spawn(async move {
+            while let Some(req) = call_rx.recv().await {
+                tokio::spawn(async move {
+                    tokio::time::sleep(delay).await;
+                    let _ = req
+                        .response_tx
+                        .send(Ok(CallToolResult::success(vec![Content::text("ok")])));
+                });
+            }
+        });
+        crate::ServerHandle {
+            slug: slug.to_string(),
+            health_rx,
+            tools: Arc::new(ArcSwap::from_pointee(vec![])),
+            call_tx,
+            cancel: tokio_util::sync::CancellationToken::new(),
+            flat_names: false,
+            status: Default::default(),
+        }
+    }
+
+    /// `slow_handle` exposing `tools`, optionally flat-named.
+    fn handle_with_tools(slug: &str, flat_names: bool, tools: &[&str]) -> crate::ServerHandle {
+        let mut handle = slow_handle(slug, std::time::Duration::ZERO);
+        handle.flat_names = flat_names;
+        let tools: Vec<Tool> = tools
+            .iter()
+            .map(|name| Tool::new(name.to_string(), "", Arc::new(serde_json::Map::new())))
+            .collect();
+        handle.tools = Arc::new(ArcSwap::from_pointee(tools));
+        handle
+    }
+
+    #[tokio::test]
+    async fn test_tools_resource_includes_flat_named_tools() {
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), handle_with_tools("a", false, &["a__x"]));
+        servers.insert(
+            "flat".to_string(),
+            handle_with_tools("flat", true, &["search"]),
+        );
+        let registry = PorterRegistry::from_handles(servers);
+
+        let tools = resource_text(
+            read_porter_resource(&registry, TOOLS_RESOURCE_URI)
+                .await
+                .unwrap(),
+        );
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&tools).unwrap(),
+            serde_json::json!({"a__x": "a", "search": "flat"})
+        );
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_calls_to_different_backends_overlap() {
+        let delay = std::time::Duration::from_millis(300);
+        let mut servers = HashMap::new();
+        servers.insert("a".to_string(), slow_handle("a", delay));
+        servers.insert("b".to_string(), slow_handle("b", delay));
+        let server = PorterMcpServer::new(PorterRegistry::from_handles(servers));
+
+        let call = |name: &'static str| {
+            let server = server.clone();
+            async move {
+                server
+                    .current_registry()
+                    .await
+                    .call_tool(name, None, None)
+                    .await
+            }
+        };
+        let started = tokio::time::Instant::now();
+        let in_flight = tokio::spawn(futures::future::join(call("a__x"), call("b__x")));
+
+        // Hot-reload can take the write lock while both calls are in flight
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let handle = server.registry_handle();
+        let write = tokio::time::timeout(std::time::Duration::from_millis(100), handle.write())
+            .await
+            .expect("registry lock must not be held across backend calls");
+        drop(write);
+
+        let (a, b) = in_flight.await.unwrap();
+        assert!(a.is_ok() && b.is_ok());
+        assert!(
+            started.elapsed() < delay * 2,
+            "calls should overlap, took {:?}",
+            started.elapsed()
+        );
+    }
+
+    /// `MakeWriter` that appends formatted log output to a shared buffer.
+    #[derive(Clone, Default)]
+    struct CaptureWriter(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CaptureWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_trace_calls_logs_matched_request_response() {
+        let capture = CaptureWriter::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::INFO)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "a".to_string(),
+            slow_handle("a", std::time::Duration::from_millis(10)),
+        );
+        let server =
+            PorterMcpServer::new(PorterRegistry::from_handles(servers)).with_call_tracing();
+        let arguments = serde_json::json!({"query": "hi", "api_token": "s3cret"});
+        server
+            .traced_call_tool("a__x", arguments.as_object().cloned(), None)
+            .await
+            .unwrap();
+
+        let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = logs.lines().filter(|l| l.contains("call_id=1")).collect();
+        assert_eq!(lines.len(), 2, "{}", logs);
+        assert!(lines[0].contains("request") && lines[0].contains("tool=a__x"));
+        assert!(lines[1].contains("response") && lines[1].contains("elapsed_ms="));
+        assert!(logs.contains(CALL_TRACE_TARGET));
+        assert!(!logs.contains("s3cret"), "arguments must be redacted");
+    }
+
+    #[tokio::test]
+    async fn test_info_tool_reports_version_config_and_health() {
+        let mut servers = HashMap::new();
+        servers.insert(
+            "a".to_string(),
+            slow_handle("a", std::time::Duration::from_millis(10)),
+        );
+        let server = PorterMcpServer::new(PorterRegistry::from_handles(servers))
+            .with_config_path("/etc/porter/porter.toml");
+
+        let tools = server.traced_list_tools().await;
+        assert!(tools.iter().any(|t| t.name == INFO_TOOL_NAME));
+
+        let result = server
+            .traced_call_tool(INFO_TOOL_NAME, None, None)
+            .await
+            .unwrap();
+        assert_eq!(result.is_error, Some(false));
+        let info = result.structured_content.expect("structured info");
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(info["config_path"], "/etc/porter/porter.toml");
+        assert_eq!(info["server_count"], 1);
+        assert_eq!(info["ready"], true);
+        assert_eq!(info["health"], serde_json::json!({"healthy": 1}));
+        assert_eq!(
+            info["servers"],
+            serde_json::json!({
+                "a": {"health": "healthy", "protocol_version": null, "degraded_reason": null}
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_allowlist_blocks_unlisted_tools() {
+        let config = PorterConfig {
+            call_allowlist: vec!["gh__*".to_string(), "docs__search".to_string()],
+            ..Default::default()
+        };
+        let server = PorterMcpServer::new(PorterRegistry::from_config(config).await.unwrap());
+
+        for blocked in ["aws__list_buckets", "docs__fetch", "gh"] {
+            let err = server
+                .traced_call_tool(blocked, None, None)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.code,
+                rmcp::model::ErrorCode::INVALID_PARAMS,
+                "{}",
+                blocked
+            );
+            assert!(err.message.contains("call_allowlist"), "{}", err.message);
+        }
+
+        // Allowed names pass the allowlist and reach registry routing, which
+        // fails here only because no such server is configured.
+        for allowed in ["gh__list_repos", "docs__search"] {
+            let err = server
+                .traced_call_tool(allowed, None, None)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.code,
+                rmcp::model::ErrorCode::INTERNAL_ERROR,
+                "{}",
+                allowed
+            );
+        }
+
+        // The built-in info tool is never subject to the allowlist
+        assert!(
+            server
+                .traced_call_tool(INFO_TOOL_NAME, None, None)
+                .await
+                .is_ok()
+        );
+    }
+
+    /// Layer keeping each closed span's name and fields, standing in for an
+    /// OpenTelemetry exporter.
+    #[derive(Clone, Default)]
+    struct SpanCapture(Arc<std::sync::Mutex<Vec<(String, SpanFields)>>>);
+
+    type SpanFields = BTreeMap<String, String>;
+
+    struct FieldVisitor<'a>(&'a mut SpanFields);
+
+    impl tracing::field::Visit for FieldVisitor<'_> {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    impl<S> tracing_subscriber::Layer<S> for SpanCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = BTreeMap::new();
+            attrs.record(&mut FieldVisitor(&mut fields));
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(fields);
+            }
+        }
+
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if let Some(span) = ctx.span(id)
+                && let Some(fields) = span.extensions_mut().get_mut::<SpanFields>()
+            {
+                values.record(&mut FieldVisitor(fields));
+            }
+        }
+
+        fn on_close(&self, id: tracing::span::Id, ctx: tracing_subscriber::layer::Context<'_, S>) {
+            if let Some(span) = ctx.span(&id) {
+                let fields = span
+                    .extensions_mut()
+                    .remove::<SpanFields>()
+                    .unwrap_or_default();
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push((span.name().to_string(), fields));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_call_tool_records_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let capture = SpanCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "a".to_string(),
+            slow_handle("a", std::time::Duration::from_millis(10)),
+        );
+        servers.insert(
+            "flat".to_string(),
+            handle_with_tools("flat", true, &["search"]),
+        );
+        let server = PorterMcpServer::new(PorterRegistry::from_handles(servers));
+        server.traced_call_tool("a__x", None, None).await.unwrap();
+        server.traced_call_tool("search", None, None).await.unwrap();
+        server.traced_list_tools().await;
+
+        let spans = capture.0.lock().unwrap().clone();
+        let calls: Vec<&SpanFields> = spans
+            .iter()
+            .filter(|(name, _)| name == "porter.call_tool")
+            .map(|(_, fields)| fields)
+            .collect();
+        let (call, flat_call) = (calls[0], calls[1]);
+        assert_eq!(flat_call["server"], "flat");
+        assert_eq!(flat_call["tool"], "search");
+        assert_eq!(call["server"], "a");
+        assert_eq!(call["tool"], "a__x");
+        assert_eq!(call["outcome"], "ok");
+        assert!(call["duration_ms"].parse::<u64>().unwrap() >= 10);
+        let (_, list) = spans
+            .iter()
+            .find(|(name, _)| name == "porter.list_tools")
+            .expect("list span recorded");
+        assert_eq!(list["tool_count"], "2");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_call_with_etag_is_not_modified() {
+        use crate::standalone::etag::{
+            ETAG_META_KEY, IF_NONE_MATCH_META_KEY, NOT_MODIFIED_META_KEY,
+        };
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "a".to_string(),
+            slow_handle("a", std::time::Duration::from_millis(1)),
+        );
+        let registry = PorterRegistry::from_handles(servers);
+        let plain = PorterMcpServer::new(registry.with_etags(false));
+        let untagged = plain.traced_call_tool("a__x", None, None).await.unwrap();
+        assert!(untagged.meta.is_none(), "ETags are off by default");
+
+        let mut servers = HashMap::new();
+        servers.insert(
+            "a".to_string(),
+            slow_handle("a", std::time::Duration::from_millis(1)),
+        );
+        let server = PorterMcpServer::new(PorterRegistry::from_handles(servers).with_etags(true));
+
+        let first = server.traced_call_tool("a__x", None, None).await.unwrap();
+        let etag = first.meta.as_ref().unwrap().0[ETAG_META_KEY].clone();
+        assert!(!first.content.is_empty());
+
+        let mut meta = Meta::new();
+        meta.0.insert(IF_NONE_MATCH_META_KEY.into(), etag.clone());
+        let second = server
+            .traced_call_tool("a__x", None, Some(meta))
+            .await
+            .unwrap();
+        let second_meta = second.meta.unwrap();
+        assert_eq!(second_meta.0[NOT_MODIFIED_META_KEY], true);
+        assert_eq!(second_meta.0[ETAG_META_KEY], etag);
+        assert_eq!(
+            second.content,
+            vec![rmcp::model::Content::text("not modified")]
+        );
     }
 
     #[tokio::test]
@@ -178,4 +948,14 @@ mod tests {
         assert!(Arc::ptr_eq(&server.peers, &peers_handle));
         assert!(Arc::ptr_eq(&server.peers, &server_clone.peers));
     }
+
+    #[tokio::test]
+    async fn test_activity_handle_is_shared() {
+        let server = make_empty_server().await;
+        let server_clone = server.clone();
+        assert!(Arc::ptr_eq(
+            &server.activity_handle(),
+            &server_clone.activity
+        ));
+    }
 }
@@ -0,0 +1,122 @@
+//! Session cap for `porter serve --max-sessions`.
+//!
+//! `LocalSessionManager` accepts any number of Streamable HTTP sessions. When a
+//! cap is configured, a request that would open a new session (a POST without
+//! an `Mcp-Session-Id` header) is answered with 503 once the cap is reached.
+//! Requests on existing sessions are never refused.
+
+use std::sync::Arc;
+
+use axum::http::{Method, Request, StatusCode};
+use axum::response::{IntoResponse, Response};
+use rmcp::transport::streamable_http_server::session::local::LocalSessionManager;
+use tokio::sync::{Mutex, MutexGuard};
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Caps the number of live sessions held by a `LocalSessionManager`.
+pub struct SessionLimit {
+    sessions: Arc<LocalSessionManager>,
+    max: usize,
+    admission: Mutex<()>,
+}
+
+impl SessionLimit {
+    /// Limit `sessions` to at most `max` live sessions.
+    pub fn new(sessions: Arc<LocalSessionManager>, max: usize) -> Self {
+        Self {
+            sessions,
+            max,
+            admission: Mutex::new(()),
+        }
+    }
+
+    /// Whether `req` would open a new session rather than use an existing one.
+    pub fn opens_session<B>(req: &Request<B>) -> bool {
+        req.method() == Method::POST && !req.headers().contains_key(SESSION_ID_HEADER)
+    }
+
+    /// Admit one new session, or return a 503 response if the cap is reached.
+    ///
+    /// The returned guard must be held until the session-opening request has
+    /// been served, so concurrent initializations cannot overshoot the cap.
+    pub async fn admit(&self) -> Result<MutexGuard<'_, ()>, Response> {
+        let guard = self.admission.lock().await;
+        let active = self.sessions.sessions.read().await.len();
+        if active >= self.max {
+            tracing::warn!(
+                active,
+                max = self.max,
+                "rejecting new MCP session: session limit reached"
+            );
+            return Err((StatusCode::SERVICE_UNAVAILABLE, "session limit reached").into_response());
+        }
+        Ok(guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{PorterMcpServer, PorterRegistry};
+    use axum::body::Body;
+    use rmcp::transport::streamable_http_server::{
+        StreamableHttpServerConfig, StreamableHttpService,
+    };
+    use std::collections::HashMap;
+    use tower::ServiceExt;
+
+    fn initialize_request() -> Request<Body> {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": "2025-03-26",
+                "capabilities": {},
+                "clientInfo": {"name": "test", "version": "1.0"}
+            }
+        });
+        Request::builder()
+            .method("POST")
+            .uri("/mcp")
+            .header("accept", "application/json, text/event-stream")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_session_over_limit_gets_503() {
+        let sessions = Arc::new(LocalSessionManager::default());
+        let server = PorterMcpServer::new(PorterRegistry::from_handles(HashMap::new()));
+        let service = StreamableHttpService::new(
+            move || Ok(server.clone()),
+            sessions.clone(),
+            StreamableHttpServerConfig::default(),
+        );
+        let limit = SessionLimit::new(sessions.clone(), 1);
+
+        let guard = limit.admit().await.expect("first session admitted");
+        let response = service.oneshot(initialize_request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        drop(guard);
+        assert_eq!(sessions.sessions.read().await.len(), 1);
+
+        let rejected = limit.admit().await.unwrap_err();
+        assert_eq!(rejected.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[test]
+    fn test_only_sessionless_posts_open_sessions() {
+        assert!(SessionLimit::opens_session(&initialize_request()));
+        let existing = Request::builder()
+            .method("POST")
+            .header(SESSION_ID_HEADER, "abc")
+            .body(())
+            .unwrap();
+        assert!(!SessionLimit::opens_session(&existing));
+        let stream = Request::builder().method("GET").body(()).unwrap();
+        assert!(!SessionLimit::opens_session(&stream));
+    }
+}
@@ -0,0 +1,79 @@
+//! Request/response call tracing for `--trace-calls`.
+//!
+//! When enabled, `PorterMcpServer` logs every `tools/list` and `tools/call` it
+//! handles as an info-level request/response pair under `CALL_TRACE_TARGET`,
+//! tied together by a per-server correlation ID and annotated with timing.
+//! Argument and result values under credential-looking keys are redacted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde_json::Value;
+
+use crate::redact::{REDACTED, is_sensitive_key};
+
+/// Tracing target for call trace events, so they can be enabled on their own
+/// (e.g. `RUST_LOG=porter::calls=info`) without the rest of Porter's logs.
+pub const CALL_TRACE_TARGET: &str = "porter::calls";
+
+/// Issues correlation IDs for traced requests.
+#[derive(Debug, Default)]
+pub struct CallTracer {
+    next_id: AtomicU64,
+}
+
+impl CallTracer {
+    /// Next correlation ID, starting at 1.
+    pub fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed) + 1
+    }
+}
+
+/// Copy of `value` with every value under a sensitive-looking key replaced.
+pub fn redact_json(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, v)| {
+                    let redacted = if is_sensitive_key(key) {
+                        Value::String(REDACTED.to_string())
+                    } else {
+                        redact_json(v)
+                    };
+                    (key.clone(), redacted)
+                })
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_json_nested() {
+        let value = json!({
+            "query": "open issues",
+            "GitHub_Token": "ghp_abc",
+            "headers": [{"Authorization": "Bearer x", "accept": "json"}]
+        });
+        assert_eq!(
+            redact_json(&value),
+            json!({
+                "query": "open issues",
+                "GitHub_Token": "[REDACTED]",
+                "headers": [{"Authorization": "[REDACTED]", "accept": "json"}]
+            })
+        );
+    }
+
+    #[test]
+    fn test_correlation_ids_increase() {
+        let tracer = CallTracer::default();
+        assert_eq!(tracer.next_id(), 1);
+        assert_eq!(tracer.next_id(), 2);
+    }
+}